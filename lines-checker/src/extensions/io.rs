@@ -0,0 +1,13 @@
+use std::io::{self, Read};
+
+/// Reads at most `limit` bytes; a longer input is cut and marked as truncated.
+pub fn read_utf8_with_limit_from<R: Read>(reader: R, limit: usize) -> io::Result<String> {
+    let truncation_message = "... (truncated output)";
+    let mut read_buffer = Vec::with_capacity(limit);
+    reader.take(limit as u64 + 1).read_to_end(&mut read_buffer)?;
+    if read_buffer.len() > limit {
+        read_buffer.truncate(limit - truncation_message.len());
+        read_buffer.extend_from_slice(truncation_message.as_bytes());
+    }
+    Ok(String::from_utf8_lossy(&read_buffer).into_owned())
+}
@@ -1,4 +1,5 @@
 use std::env;
+use std::io;
 use std::iter;
 use std::path::PathBuf;
 
@@ -8,25 +9,59 @@ extern crate log;
 #[macro_use]
 extern crate failure;
 use failure::ResultExt;
+#[macro_use]
+extern crate serde;
+extern crate serde_json;
 
 extern crate lines_game_engine;
-use lines_game_engine::{GameBoard, GameBoardMove, GameScore, GAME_BOARD_SIZE};
+use lines_game_engine::{GameBoard, GameBoardMove, GameError, GameOverReason, GameScore, RuleSet,
+                        GAME_BOARD_SIZE, GAME_OVER_PREFIX};
 
 mod extensions;
 use self::extensions::fs::read_utf8_with_limit;
+use self::extensions::io::read_utf8_with_limit_from;
+mod modes;
+use self::modes::CheckerMode;
+mod report;
+use self::report::{CheckerReport, FailureReason};
+mod scoring;
+use self::scoring::ScoringPolicy;
+#[cfg(test)]
+mod testing;
 
 #[derive(Debug)]
 enum CheckerVerdict {
     OK,
     WrongAnswer(String),
     PresentationError(String),
+    /// The partial credit from 0 to 1 for the expected score that is not reached.
+    Points(f64),
+    /// The jury files (or the checker arguments) are broken, so the solution cannot be judged.
+    Fail(String),
+}
+
+/// Formats the error with all its causes, e.g. "The test input file could not be read: No such
+/// file or directory".
+fn error_chain(why: &failure::Error) -> String {
+    why.causes()
+        .map(|cause| cause.to_string())
+        .collect::<Vec<String>>()
+        .join(": ")
+}
+
+#[derive(Debug)]
+enum SolutionOutput {
+    File(PathBuf),
+    /// DOMjudge pipes the team output to the validator.
+    Stdin,
 }
 
 #[derive(Debug)]
 struct Config {
     test_input_filepath: PathBuf,
     test_answer_filepath: PathBuf,
-    solution_output_filepath: PathBuf,
+    solution_output: SolutionOutput,
+    rules: RuleSet,
 }
 
 impl Config {
@@ -46,16 +81,39 @@ impl Config {
                 format_err!("The third argument should be a path to the test answer file.")
             })?
             .into();
+        Self::new(
+            test_input_filepath,
+            test_answer_filepath,
+            SolutionOutput::File(solution_output_filepath),
+        )
+    }
+
+    fn new(
+        test_input_filepath: PathBuf,
+        test_answer_filepath: PathBuf,
+        solution_output: SolutionOutput,
+    ) -> Result<Self, failure::Error> {
+        let rules = match env::var("RULE_SET") {
+            Ok(rules) => rules
+                .parse::<RuleSet>()
+                .context("RULE_SET environment variable is not a valid rule set")?,
+            Err(_) => RuleSet::default(),
+        };
         Ok(Self {
             test_input_filepath,
             test_answer_filepath,
-            solution_output_filepath,
+            solution_output,
+            rules,
         })
     }
 
     fn player_move(&self) -> Result<Option<GameBoardMove>, failure::Error> {
-        let solution_output = read_utf8_with_limit(&self.solution_output_filepath, 1000)
-            .context("The solution output file could not be read")?;
+        let solution_output = match self.solution_output {
+            SolutionOutput::File(ref solution_output_filepath) => {
+                read_utf8_with_limit(solution_output_filepath, 1000)
+            }
+            SolutionOutput::Stdin => read_utf8_with_limit_from(io::stdin(), 1000),
+        }.context("The solution output could not be read")?;
         Ok(if solution_output.is_empty() {
             None
         } else {
@@ -63,69 +121,141 @@ impl Config {
         })
     }
 
-    fn game_state(&self) -> (GameBoard, GameScore) {
+    /// The game board, the score and the reason the game is over (from the `GAME OVER <reason>`
+    /// line; the inputs of the old generators only tell that by a full board).
+    fn game_state(&self) -> Result<(GameBoard, GameScore, Option<GameOverReason>), failure::Error> {
         let test_input = read_utf8_with_limit(&self.test_input_filepath, 1000)
-            .expect("The test input file could not be read.");
+            .context("The test input file could not be read")?;
         let mut test_input_lines = test_input.lines();
-        (
-            (&mut test_input_lines)
+        let game_board = GameBoard::parse_with_rules(
+            &(&mut test_input_lines)
                 .take(GAME_BOARD_SIZE)
                 .collect::<Vec<&str>>()
-                .join("\n")
-                .parse::<GameBoard>()
-                .expect("Game Board could not be parsed."),
-            test_input_lines
-                .skip(1)
-                .next()
-                .expect("Game Score is missing.")
-                .parse::<GameScore>()
-                .expect("Game Score could not be parsed"),
-        )
+                .join("\n"),
+            self.rules,
+        ).context("The game board in the test input could not be parsed")?;
+        let game_score = test_input_lines
+            .nth(1)
+            .ok_or_else(|| format_err!("The game score is missing in the test input"))?
+            .parse::<GameScore>()
+            .context("The game score in the test input could not be parsed")?;
+        let game_over_reason = match test_input_lines
+            .find_map(|line| line.strip_prefix(GAME_OVER_PREFIX))
+        {
+            Some(game_over_reason_str) => Some(game_over_reason_str
+                .parse::<GameOverReason>()
+                .context("The game over reason in the test input could not be parsed")?),
+            None if game_board.is_full() => Some(GameOverReason::NoSpace),
+            None => None,
+        };
+        Ok((game_board, game_score, game_over_reason))
     }
 
-    fn expected_game_score(&self) -> GameScore {
-        read_utf8_with_limit(&self.test_answer_filepath, 1000)
-            .expect("The test answer file could not be read.")
-            .trim_end()
-            .parse::<GameScore>()
-            .expect("The test answer file should be empty or contain an integer indicating the expected score.")
+    /// The expected score and the optional scoring policy, e.g. `120 power:2`; an empty answer
+    /// expects nothing.
+    fn expected_game_score(&self) -> Result<(GameScore, ScoringPolicy), failure::Error> {
+        let test_answer = read_utf8_with_limit(&self.test_answer_filepath, 1000)
+            .context("The test answer file could not be read")?;
+        let mut test_answer_words = test_answer.split_whitespace();
+        let expected_game_score = match test_answer_words.next() {
+            Some(expected_game_score_str) => expected_game_score_str
+                .parse::<GameScore>()
+                .context(
+                    "The test answer file should be empty or contain an integer indicating the \
+                     expected score",
+                )?,
+            None => GameScore::new(0),
+        };
+        let scoring_policy = match test_answer_words.next() {
+            Some(scoring_policy_str) => scoring_policy_str
+                .parse::<ScoringPolicy>()
+                .context("The scoring policy in the test answer file is invalid")?,
+            None => ScoringPolicy::default(),
+        };
+        if let Some(word) = test_answer_words.next() {
+            bail!("Unexpected '{}' after the scoring policy in the test answer file", word);
+        }
+        Ok((expected_game_score, scoring_policy))
     }
 
-    fn check_solution_output(&self) -> CheckerVerdict {
-        let (mut game_board, mut game_score) = self.game_state();
+    /// Judges the solution output; the details of the check are collected in the report.
+    fn check_solution_output(&self, report: &mut CheckerReport) -> CheckerVerdict {
+        let (mut game_board, mut game_score, game_over_reason) = match self.game_state() {
+            Ok(game_state) => game_state,
+            Err(why) => return CheckerVerdict::Fail(error_chain(&why)),
+        };
+        report.game_board_before = Some(game_board.clone());
+        report.game_score = Some(game_score);
+        report.game_over_reason = game_over_reason;
+        let (expected_game_score, scoring_policy) = match self.expected_game_score() {
+            Ok(expected_game_score_and_scoring_policy) => expected_game_score_and_scoring_policy,
+            Err(why) => return CheckerVerdict::Fail(error_chain(&why)),
+        };
+        report.set_scoring(expected_game_score, scoring_policy);
 
-        if !game_board.is_full() {
+        if let Some(game_over_reason) = game_over_reason {
+            info!(
+                "The game is over ({}), so no move is expected.",
+                game_over_reason
+            );
+        } else {
             let player_move = match self.player_move() {
                 Ok(player_move) => player_move,
                 Err(why) => {
-                    return CheckerVerdict::PresentationError(why.to_string())
+                    report.failure_reason = Some(FailureReason::InvalidOutputFormat {
+                        message: error_chain(&why),
+                        error: why.causes()
+                            .filter_map(|cause| cause.downcast_ref::<GameError>())
+                            .next()
+                            .cloned(),
+                    });
+                    return CheckerVerdict::PresentationError(why.to_string());
                 }
             };
+            report.player_move = player_move;
             if let Some(player_move) = player_move {
                 let player_move_score = match game_board.move_ball(&player_move) {
-                    Ok(score) => score,
+                    Ok(move_outcome) => move_outcome.score,
                     Err(why) => {
-                        return CheckerVerdict::WrongAnswer(format!(
-                            "The move ({:?}) could not be made: {:?}",
+                        let verdict = CheckerVerdict::WrongAnswer(format!(
+                            "The move ({:?}) could not be made: {}",
                             player_move, why
                         ));
+                        report.failure_reason = Some(FailureReason::InvalidMove { error: why });
+                        return verdict;
                     }
                 };
                 game_score += player_move_score;
+                report.score_gained = Some(player_move_score);
+                report.game_board_after = Some(game_board.clone());
             } else {
+                report.failure_reason = Some(FailureReason::NoMove);
                 return CheckerVerdict::WrongAnswer(
-                    "The player did not make any move, but the board is not full yet.".into(),
+                    "The player did not make any move, but the game is not over yet.".into(),
                 );
             }
         }
 
-        let expected_game_score = self.expected_game_score();
-            
         if game_score < expected_game_score {
-            return CheckerVerdict::WrongAnswer(format!(
-                "The expected game score ({}) is not reached ({})",
-                expected_game_score, game_score
-            ));
+            report.failure_reason = Some(FailureReason::ExpectedScoreNotReached);
+            if let Some(points) = scoring_policy.points(game_score, expected_game_score) {
+                info!(
+                    "The expected game score ({}) is not reached ({}), the {} scoring gives {} \
+                     points",
+                    expected_game_score, game_score, scoring_policy, points
+                );
+                return CheckerVerdict::Points(points);
+            }
+            return CheckerVerdict::WrongAnswer(match game_over_reason {
+                Some(game_over_reason) => format!(
+                    "The expected game score ({}) is not reached ({}), the game is over ({})",
+                    expected_game_score, game_score, game_over_reason
+                ),
+                None => format!(
+                    "The expected game score ({}) is not reached ({})",
+                    expected_game_score, game_score
+                ),
+            });
         }
 
         CheckerVerdict::OK
@@ -135,21 +265,35 @@ impl Config {
 fn main() {
     env_logger::Builder::from_default_env().parse("info").init();
 
-    let checker = Config::from_args(&mut env::args().skip(1))
-        .expect("Checker input arguments are not valid");
-
-    std::process::exit(match checker.check_solution_output() {
-        CheckerVerdict::OK => {
-            info!("OK");
-            0
-        }
-        CheckerVerdict::WrongAnswer(msg) => {
-            info!("Wrong Answer due to {}", msg);
-            1
-        }
-        CheckerVerdict::PresentationError(msg) => {
-            info!("Presentation Error due to {}", msg);
-            2
+    let mut report = CheckerReport::default();
+    // The mode errors are reported in the classic way, since the mode is unknown.
+    let (checker_mode, mut verdict) =
+        match CheckerMode::from_env_and_args(&mut env::args().skip(1)) {
+            Ok((checker_mode, Ok(checker))) => {
+                let verdict = checker.check_solution_output(&mut report);
+                (checker_mode, verdict)
+            }
+            Ok((checker_mode, Err(why))) => (
+                checker_mode,
+                CheckerVerdict::Fail(format!(
+                    "Checker input arguments are not valid: {}",
+                    error_chain(&why)
+                )),
+            ),
+            Err(why) => (
+                CheckerMode::Classic,
+                CheckerVerdict::Fail(format!(
+                    "Checker mode is not valid: {}",
+                    error_chain(&why)
+                )),
+            ),
+        };
+    if let Some(checker_report_filepath) = env::var_os("CHECKER_REPORT") {
+        report.set_verdict(&verdict);
+        if let Err(why) = report.save(checker_report_filepath) {
+            verdict = CheckerVerdict::Fail(error_chain(&why));
         }
-    });
+    }
+
+    std::process::exit(checker_mode.report(&verdict));
 }
@@ -0,0 +1,106 @@
+use std::fs;
+use std::io::Write;
+use std::iter;
+use std::path::{Path, PathBuf};
+
+use failure::{self, ResultExt};
+
+use super::super::{error_chain, CheckerVerdict, Config, SolutionOutput};
+
+/// The exit codes of a DOMjudge output validator; any other code is a judging error.
+const ACCEPTED_EXIT_CODE: i32 = 42;
+const WRONG_ANSWER_EXIT_CODE: i32 = 43;
+const JUDGE_ERROR_EXIT_CODE: i32 = 1;
+
+/// Reports the verdict the way DOMjudge output validators do: `lines-checker <input> <answer>
+/// <feedback dir> [<flags>...] < <team output>`. The verdict message goes to
+/// `judgemessage.txt`, and the reason of a rejection also to `teammessage.txt`. Partial credit is
+/// accepted with the points in `score.txt`, as in the Kattis problem format.
+#[derive(Debug)]
+pub struct DomjudgeReport {
+    feedback_dirpath: PathBuf,
+}
+
+impl DomjudgeReport {
+    pub fn from_args<I: iter::Iterator<Item = String>>(
+        args: &mut I,
+    ) -> Result<(Self, Result<Config, failure::Error>), failure::Error> {
+        match (args.next(), args.next(), args.next()) {
+            // The validator flags of the problem (e.g. `case_sensitive`) do not matter for a move.
+            (Some(test_input_filepath), Some(test_answer_filepath), Some(feedback_dirpath)) => Ok((
+                Self {
+                    feedback_dirpath: feedback_dirpath.into(),
+                },
+                Config::new(
+                    test_input_filepath.into(),
+                    test_answer_filepath.into(),
+                    SolutionOutput::Stdin,
+                ),
+            )),
+            _ => bail!(
+                "DOMjudge validator arguments are expected to be the test input file, the test \
+                 answer file and the feedback directory"
+            ),
+        }
+    }
+
+    pub fn report(&self, verdict: &CheckerVerdict) -> i32 {
+        let (exit_code, judge_message, team_message) = match *verdict {
+            CheckerVerdict::OK => (ACCEPTED_EXIT_CODE, "OK".to_string(), None),
+            CheckerVerdict::WrongAnswer(ref msg) => (
+                WRONG_ANSWER_EXIT_CODE,
+                format!("Wrong Answer: {}", msg),
+                Some(msg),
+            ),
+            CheckerVerdict::PresentationError(ref msg) => (
+                WRONG_ANSWER_EXIT_CODE,
+                format!("Presentation Error: {}", msg),
+                Some(msg),
+            ),
+            CheckerVerdict::Points(points) => {
+                if let Err(why) =
+                    write_feedback(&self.feedback_dirpath, "score.txt", &points.to_string())
+                {
+                    error!("{}", error_chain(&why));
+                    return JUDGE_ERROR_EXIT_CODE;
+                }
+                (ACCEPTED_EXIT_CODE, format!("Points: {}", points), None)
+            }
+            CheckerVerdict::Fail(ref msg) => {
+                (JUDGE_ERROR_EXIT_CODE, format!("Fail: {}", msg), None)
+            }
+        };
+        let written = write_feedback(&self.feedback_dirpath, "judgemessage.txt", &judge_message)
+            .and_then(|_| match team_message {
+                Some(team_message) => {
+                    write_feedback(&self.feedback_dirpath, "teammessage.txt", team_message)
+                }
+                None => Ok(()),
+            });
+        if let Err(why) = written {
+            error!("{}", error_chain(&why));
+            return JUDGE_ERROR_EXIT_CODE;
+        }
+        exit_code
+    }
+}
+
+fn write_feedback(
+    feedback_dirpath: &Path,
+    feedback_filename: &str,
+    message: &str,
+) -> Result<(), failure::Error> {
+    let feedback_filepath = feedback_dirpath.join(feedback_filename);
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&feedback_filepath)
+        .and_then(|mut file| writeln!(file, "{}", message))
+        .with_context(|_| {
+            format!(
+                "The feedback file {:?} could not be written",
+                feedback_filepath
+            )
+        })?;
+    Ok(())
+}
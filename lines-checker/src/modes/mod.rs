@@ -0,0 +1,90 @@
+use std::env;
+use std::iter;
+
+use failure;
+
+use super::{CheckerVerdict, Config};
+
+#[cfg(test)]
+mod tests;
+
+pub mod domjudge;
+use self::domjudge::DomjudgeReport;
+pub mod testlib;
+use self::testlib::TestlibReport;
+
+/// How the verdict is reported to the judging system; selected by the `CHECKER_MODE`
+/// environment variable.
+#[derive(Debug)]
+pub enum CheckerMode {
+    /// Exit codes 0 (OK), 1 (WA), 2 (PE), 3 (Fail) and 7 (Points, printed to stdout), and the
+    /// verdict message in the log.
+    Classic,
+    Testlib(TestlibReport),
+    Domjudge(DomjudgeReport),
+}
+
+impl CheckerMode {
+    /// Reads the mode from the environment and the checker arguments in the order of the mode.
+    /// The `Config` error is kept to be reported as a verdict in the way of the mode.
+    pub fn from_env_and_args<I: iter::Iterator<Item = String>>(
+        args: &mut I,
+    ) -> Result<(Self, Result<Config, failure::Error>), failure::Error> {
+        match env::var("CHECKER_MODE") {
+            Err(_) => Ok((CheckerMode::Classic, Self::classic_config(args))),
+            Ok(ref mode) if mode == "classic" => {
+                Ok((CheckerMode::Classic, Self::classic_config(args)))
+            }
+            Ok(ref mode) if mode == "testlib" => {
+                let config = Config::from_args(args);
+                Ok((CheckerMode::Testlib(TestlibReport::from_args(args)?), config))
+            }
+            Ok(ref mode) if mode == "domjudge" => {
+                let (domjudge_report, config) = DomjudgeReport::from_args(args)?;
+                Ok((CheckerMode::Domjudge(domjudge_report), config))
+            }
+            Ok(mode) => bail!(
+                "CHECKER_MODE '{}' is not supported (classic, testlib or domjudge are)",
+                mode
+            ),
+        }
+    }
+
+    /// The classic checker takes nothing but the three file paths.
+    fn classic_config<I: iter::Iterator<Item = String>>(
+        args: &mut I,
+    ) -> Result<Config, failure::Error> {
+        let config = Config::from_args(args)?;
+        if let Some(arg) = args.next() {
+            bail!("Unexpected checker argument '{}'", arg);
+        }
+        Ok(config)
+    }
+
+    /// Logs the verdict, reports it in the way of the mode and returns the exit code.
+    pub fn report(&self, verdict: &CheckerVerdict) -> i32 {
+        match *verdict {
+            CheckerVerdict::OK => info!("OK"),
+            CheckerVerdict::WrongAnswer(ref msg) => info!("Wrong Answer due to {}", msg),
+            CheckerVerdict::PresentationError(ref msg) => {
+                info!("Presentation Error due to {}", msg)
+            }
+            CheckerVerdict::Points(points) => info!("Points: {}", points),
+            CheckerVerdict::Fail(ref msg) => error!("Fail (judge error) due to {}", msg),
+        }
+        match *self {
+            CheckerMode::Classic => match *verdict {
+                CheckerVerdict::OK => 0,
+                CheckerVerdict::WrongAnswer(_) => 1,
+                CheckerVerdict::PresentationError(_) => 2,
+                CheckerVerdict::Fail(_) => 3,
+                CheckerVerdict::Points(points) => {
+                    println!("{}", points);
+                    7
+                }
+            },
+            CheckerMode::Testlib(ref testlib_report) => testlib_report.report(verdict),
+            CheckerMode::Domjudge(ref domjudge_report) => domjudge_report.report(verdict),
+        }
+    }
+}
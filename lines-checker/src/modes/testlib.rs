@@ -0,0 +1,115 @@
+use std::fs;
+use std::io::{self, Write};
+use std::iter;
+use std::path::PathBuf;
+
+use failure;
+
+use super::super::CheckerVerdict;
+
+/// The exit codes of testlib (`_ok`, `_wa`, `_pe`, `_fail`, `_points`).
+const OK_EXIT_CODE: i32 = 0;
+const WRONG_ANSWER_EXIT_CODE: i32 = 1;
+const PRESENTATION_ERROR_EXIT_CODE: i32 = 2;
+const FAIL_EXIT_CODE: i32 = 3;
+const POINTS_EXIT_CODE: i32 = 7;
+
+/// Reports the verdict the way testlib checkers do: `check <input> <output> <answer>
+/// [<result file> [-appes]]`. The verdict message goes to stderr and to the result file, which
+/// gets the XML `<result outcome="...">` form with `-appes`.
+#[derive(Debug)]
+pub struct TestlibReport {
+    result_filepath: Option<PathBuf>,
+    is_xml: bool,
+}
+
+impl TestlibReport {
+    pub fn from_args<I: iter::Iterator<Item = String>>(
+        args: &mut I,
+    ) -> Result<Self, failure::Error> {
+        let result_filepath = args.next().map(PathBuf::from);
+        let is_xml = match args.next() {
+            None => false,
+            Some(ref flag) if flag == "-appes" || flag == "-APPES" => true,
+            Some(flag) => bail!("'{}' is not a testlib checker flag (-appes is)", flag),
+        };
+        if let Some(arg) = args.next() {
+            bail!("Unexpected checker argument '{}'", arg);
+        }
+        Ok(Self {
+            result_filepath,
+            is_xml,
+        })
+    }
+
+    pub fn report(&self, verdict: &CheckerVerdict) -> i32 {
+        let (exit_code, outcome, text_prefix, msg) = match *verdict {
+            CheckerVerdict::OK => (OK_EXIT_CODE, "accepted", "ok", String::new()),
+            CheckerVerdict::WrongAnswer(ref msg) => {
+                (WRONG_ANSWER_EXIT_CODE, "wrong-answer", "wrong answer", msg.clone())
+            }
+            CheckerVerdict::PresentationError(ref msg) => (
+                PRESENTATION_ERROR_EXIT_CODE,
+                "presentation-error",
+                "wrong output format",
+                msg.clone(),
+            ),
+            CheckerVerdict::Points(points) => {
+                (POINTS_EXIT_CODE, "points", "points", points.to_string())
+            }
+            CheckerVerdict::Fail(ref msg) => (FAIL_EXIT_CODE, "fail", "FAIL", msg.clone()),
+        };
+        let text_report = if msg.is_empty() {
+            text_prefix.to_string()
+        } else {
+            format!("{} {}", text_prefix, msg)
+        };
+        eprintln!("{}", text_report);
+        if let Some(ref result_filepath) = self.result_filepath {
+            let report = if self.is_xml {
+                let points_attribute = match *verdict {
+                    CheckerVerdict::Points(points) => format!(" points=\"{}\"", points),
+                    _ => String::new(),
+                };
+                format!(
+                    "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+                     <result outcome=\"{}\"{}>{}</result>\n",
+                    outcome,
+                    points_attribute,
+                    xml_escape(&msg)
+                )
+            } else {
+                text_report + "\n"
+            };
+            if let Err(why) = fs::File::create(result_filepath)
+                .and_then(|mut file| file.write_all(report.as_bytes()))
+            {
+                return self.report_failed(result_filepath, &why);
+            }
+        }
+        exit_code
+    }
+
+    fn report_failed(&self, result_filepath: &PathBuf, why: &io::Error) -> i32 {
+        error!(
+            "The result file {:?} could not be written: {}",
+            result_filepath, why
+        );
+        FAIL_EXIT_CODE
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
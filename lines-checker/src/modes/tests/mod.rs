@@ -0,0 +1,184 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::super::testing::TemporaryDirectory;
+use super::super::{CheckerVerdict, SolutionOutput};
+use super::domjudge::DomjudgeReport;
+use super::testlib::TestlibReport;
+
+fn testlib_report(args: &[&str]) -> TestlibReport {
+    TestlibReport::from_args(&mut args.iter().map(|arg| arg.to_string())).unwrap()
+}
+
+fn verdicts() -> Vec<CheckerVerdict> {
+    vec![
+        CheckerVerdict::OK,
+        CheckerVerdict::WrongAnswer("the score is 3".to_string()),
+        CheckerVerdict::PresentationError("no move".to_string()),
+        CheckerVerdict::Fail("no answer file".to_string()),
+        CheckerVerdict::Points(0.25),
+    ]
+}
+
+#[test]
+fn testlib_report_uses_testlib_exit_codes_and_messages() {
+    let temporary_directory = TemporaryDirectory::new("testlib-result");
+    let result_filepath = temporary_directory.join("result.txt");
+    let report = testlib_report(&[result_filepath.to_str().unwrap()]);
+    let expected_reports = [
+        (0, "ok\n"),
+        (1, "wrong answer the score is 3\n"),
+        (2, "wrong output format no move\n"),
+        (3, "FAIL no answer file\n"),
+        (7, "points 0.25\n"),
+    ];
+    for (verdict, &(exit_code, result)) in verdicts().iter().zip(&expected_reports) {
+        assert_eq!(report.report(verdict), exit_code, "{:?}", verdict);
+        assert_eq!(fs::read_to_string(&result_filepath).unwrap(), result);
+    }
+}
+
+#[test]
+fn testlib_report_writes_xml_with_appes() {
+    let temporary_directory = TemporaryDirectory::new("testlib-xml-result");
+    let result_filepath = temporary_directory.join("result.xml");
+    let report = testlib_report(&[result_filepath.to_str().unwrap(), "-appes"]);
+    let expected_reports = [
+        (0, r#"<result outcome="accepted"></result>"#),
+        (1, r#"<result outcome="wrong-answer">the score is 3</result>"#),
+        (2, r#"<result outcome="presentation-error">no move</result>"#),
+        (3, r#"<result outcome="fail">no answer file</result>"#),
+        (7, r#"<result outcome="points" points="0.25">0.25</result>"#),
+    ];
+    for (verdict, &(exit_code, result)) in verdicts().iter().zip(&expected_reports) {
+        assert_eq!(report.report(verdict), exit_code, "{:?}", verdict);
+        assert_eq!(
+            fs::read_to_string(&result_filepath).unwrap(),
+            format!("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n{}\n", result)
+        );
+    }
+
+    report.report(&CheckerVerdict::WrongAnswer(
+        "<move> & \"score\" 'x'".to_string(),
+    ));
+    assert!(
+        fs::read_to_string(&result_filepath)
+            .unwrap()
+            .contains(">&lt;move&gt; &amp; &quot;score&quot; &apos;x&apos;</result>")
+    );
+}
+
+#[test]
+fn testlib_report_works_without_result_file() {
+    let report = testlib_report(&[]);
+    assert_eq!(report.report(&CheckerVerdict::OK), 0);
+    assert_eq!(report.report(&CheckerVerdict::Points(0.5)), 7);
+}
+
+#[test]
+fn testlib_report_fails_when_result_file_cannot_be_written() {
+    let temporary_directory = TemporaryDirectory::new("testlib-unwritable-result");
+    let result_filepath = temporary_directory.join("missing-dir").join("result.txt");
+    let report = testlib_report(&[result_filepath.to_str().unwrap()]);
+    assert_eq!(report.report(&CheckerVerdict::OK), 3);
+}
+
+#[test]
+fn testlib_report_rejects_unknown_arguments() {
+    let args = ["result.txt", "-xml"];
+    assert!(TestlibReport::from_args(&mut args.iter().map(|arg| arg.to_string())).is_err());
+    let args = ["result.txt", "-appes", "extra"];
+    assert!(TestlibReport::from_args(&mut args.iter().map(|arg| arg.to_string())).is_err());
+}
+
+fn domjudge_report(feedback_dirpath: &Path) -> DomjudgeReport {
+    let args = [
+        "input.txt",
+        "answer.txt",
+        feedback_dirpath.to_str().unwrap(),
+        "case_sensitive",
+    ];
+    let (report, config) =
+        DomjudgeReport::from_args(&mut args.iter().map(|arg| arg.to_string())).unwrap();
+    let config = config.unwrap();
+    assert_eq!(config.test_input_filepath, PathBuf::from("input.txt"));
+    assert_eq!(config.test_answer_filepath, PathBuf::from("answer.txt"));
+    match config.solution_output {
+        SolutionOutput::Stdin => (),
+        SolutionOutput::File(_) => panic!("DOMjudge passes the team output on stdin"),
+    }
+    report
+}
+
+/// The feedback files the report has written, in the name order.
+fn feedback_files(feedback_dirpath: &Path) -> Vec<(String, String)> {
+    let mut feedback_files = fs::read_dir(feedback_dirpath)
+        .unwrap()
+        .map(|entry| {
+            let path = entry.unwrap().path();
+            (
+                path.file_name().unwrap().to_str().unwrap().to_string(),
+                fs::read_to_string(&path).unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    feedback_files.sort();
+    feedback_files
+}
+
+#[test]
+fn domjudge_report_uses_validator_exit_codes_and_feedback_files() {
+    let expected_reports: Vec<(i32, Vec<(&str, &str)>)> = vec![
+        (42, vec![("judgemessage.txt", "OK\n")]),
+        (
+            43,
+            vec![
+                ("judgemessage.txt", "Wrong Answer: the score is 3\n"),
+                ("teammessage.txt", "the score is 3\n"),
+            ],
+        ),
+        (
+            43,
+            vec![
+                ("judgemessage.txt", "Presentation Error: no move\n"),
+                ("teammessage.txt", "no move\n"),
+            ],
+        ),
+        (1, vec![("judgemessage.txt", "Fail: no answer file\n")]),
+        (
+            42,
+            vec![
+                ("judgemessage.txt", "Points: 0.25\n"),
+                ("score.txt", "0.25\n"),
+            ],
+        ),
+    ];
+    for (verdict, (exit_code, files)) in verdicts().iter().zip(expected_reports) {
+        let temporary_directory = TemporaryDirectory::new("domjudge-feedback");
+        let feedback_dirpath = temporary_directory.join("feedback");
+        fs::create_dir(&feedback_dirpath).unwrap();
+        let report = domjudge_report(&feedback_dirpath);
+        assert_eq!(report.report(verdict), exit_code, "{:?}", verdict);
+        assert_eq!(
+            feedback_files(&feedback_dirpath),
+            files
+                .iter()
+                .map(|&(name, contents)| (name.to_string(), contents.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn domjudge_report_fails_when_feedback_cannot_be_written() {
+    let temporary_directory = TemporaryDirectory::new("domjudge-missing-feedback");
+    let report = domjudge_report(&temporary_directory.join("missing-feedback-dir"));
+    assert_eq!(report.report(&CheckerVerdict::OK), 1);
+    assert_eq!(report.report(&CheckerVerdict::Points(0.5)), 1);
+}
+
+#[test]
+fn domjudge_report_requires_feedback_dir() {
+    let args = ["input.txt", "answer.txt"];
+    assert!(DomjudgeReport::from_args(&mut args.iter().map(|arg| arg.to_string())).is_err());
+}
@@ -0,0 +1,90 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use failure::{self, ResultExt};
+use serde_json;
+
+use lines_game_engine::{GameBoard, GameBoardMove, GameError, GameOverReason, GameScore};
+
+use super::CheckerVerdict;
+use super::scoring::ScoringPolicy;
+
+#[cfg(test)]
+mod tests;
+
+/// Why the solution output is not accepted (or could not be judged).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FailureReason {
+    /// The jury files, the arguments or the environment of the checker are broken.
+    JuryError { message: String },
+    /// The solution output is not a move; the error is missing if the output could not even be
+    /// read.
+    InvalidOutputFormat {
+        message: String,
+        error: Option<GameError>,
+    },
+    /// The solution output is empty, but the game is not over.
+    NoMove,
+    /// The move is not possible on the game board.
+    InvalidMove { error: GameError },
+    ExpectedScoreNotReached,
+}
+
+/// The verdict and everything the checker has learned about the solution output, written as JSON
+/// to the `CHECKER_REPORT` file.
+#[derive(Debug, Default, Serialize)]
+pub struct CheckerReport {
+    /// `ok`, `wrong_answer`, `presentation_error`, `points` or `fail`.
+    pub verdict: &'static str,
+    pub points: Option<f64>,
+    pub failure_reason: Option<FailureReason>,
+    pub player_move: Option<GameBoardMove>,
+    pub game_board_before: Option<GameBoard>,
+    pub game_board_after: Option<GameBoard>,
+    pub game_over_reason: Option<GameOverReason>,
+    /// The score before the move.
+    pub game_score: Option<GameScore>,
+    pub score_gained: Option<GameScore>,
+    pub expected_game_score: Option<GameScore>,
+    pub scoring_policy: Option<String>,
+}
+
+impl CheckerReport {
+    pub fn set_scoring(&mut self, expected_game_score: GameScore, scoring_policy: ScoringPolicy) {
+        self.expected_game_score = Some(expected_game_score);
+        self.scoring_policy = Some(scoring_policy.to_string());
+    }
+
+    /// Records the verdict; the failure reason is expected to be set along with the verdict,
+    /// except for the jury errors which can happen before the check starts.
+    pub fn set_verdict(&mut self, verdict: &CheckerVerdict) {
+        self.verdict = match *verdict {
+            CheckerVerdict::OK => "ok",
+            CheckerVerdict::WrongAnswer(_) => "wrong_answer",
+            CheckerVerdict::PresentationError(_) => "presentation_error",
+            CheckerVerdict::Points(points) => {
+                self.points = Some(points);
+                "points"
+            }
+            CheckerVerdict::Fail(ref msg) => {
+                if self.failure_reason.is_none() {
+                    self.failure_reason = Some(FailureReason::JuryError {
+                        message: msg.clone(),
+                    });
+                }
+                "fail"
+            }
+        };
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), failure::Error> {
+        let path = path.as_ref();
+        let report_str = serde_json::to_string_pretty(self)? + "\n";
+        fs::File::create(path)
+            .and_then(|mut file| file.write_all(report_str.as_bytes()))
+            .with_context(|_| format!("The checker report file {:?} could not be written", path))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use serde_json;
+
+use super::super::CheckerVerdict;
+use super::{CheckerReport, FailureReason};
+
+#[test]
+fn jury_error_before_the_check_is_reported() {
+    let mut report = CheckerReport::default();
+    report.set_verdict(&CheckerVerdict::Fail(
+        "Checker mode is not valid".to_string(),
+    ));
+    let report_value = serde_json::to_value(&report).unwrap();
+    assert_eq!(report_value["verdict"], "fail");
+    assert_eq!(report_value["failure_reason"]["kind"], "jury_error");
+    assert_eq!(
+        report_value["failure_reason"]["message"],
+        "Checker mode is not valid"
+    );
+    assert!(report_value["player_move"].is_null());
+}
+
+#[test]
+fn failure_reason_of_the_check_is_kept() {
+    let mut report = CheckerReport {
+        failure_reason: Some(FailureReason::ExpectedScoreNotReached),
+        ..CheckerReport::default()
+    };
+    report.set_verdict(&CheckerVerdict::Points(0.5));
+    let report_value = serde_json::to_value(&report).unwrap();
+    assert_eq!(report_value["verdict"], "points");
+    assert_eq!(report_value["points"], 0.5);
+    assert_eq!(
+        report_value["failure_reason"]["kind"],
+        "expected_score_not_reached"
+    );
+}
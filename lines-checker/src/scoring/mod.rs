@@ -0,0 +1,77 @@
+use std::fmt;
+use std::str;
+
+use failure;
+
+use lines_game_engine::GameScore;
+
+#[cfg(test)]
+mod tests;
+
+/// How the score reached by the player is turned into a verdict when it is below the expected
+/// one. A reached expected score is always OK.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum ScoringPolicy {
+    /// Wrong Answer below the expected score.
+    #[default]
+    Binary,
+    /// The share of the expected score raised to the power (1 for the plain share), as points
+    /// from 0 to 1.
+    Power(f64),
+}
+
+impl ScoringPolicy {
+    /// The points for the score below the expected one; `None` for the binary policy.
+    pub fn points(&self, game_score: GameScore, expected_game_score: GameScore) -> Option<f64> {
+        match *self {
+            ScoringPolicy::Binary => None,
+            ScoringPolicy::Power(exponent) => {
+                let game_score: u32 = game_score.into();
+                let expected_game_score: u32 = expected_game_score.into();
+                let share = if expected_game_score == 0 {
+                    1.0
+                } else {
+                    f64::from(game_score) / f64::from(expected_game_score)
+                };
+                Some(share.min(1.0).powf(exponent))
+            }
+        }
+    }
+}
+
+impl fmt::Display for ScoringPolicy {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ScoringPolicy::Binary => write!(fmt, "binary"),
+            ScoringPolicy::Power(1.0) => write!(fmt, "linear"),
+            ScoringPolicy::Power(exponent) => write!(fmt, "power:{}", exponent),
+        }
+    }
+}
+
+impl str::FromStr for ScoringPolicy {
+    type Err = failure::Error;
+
+    /// Parses `binary`, `linear` or `power:<exponent>` (a positive exponent; below 1 the curve
+    /// is generous to the low scores, above 1 it rewards getting close to the target).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut policy_parts = s.splitn(2, ':');
+        match (policy_parts.next(), policy_parts.next()) {
+            (Some("binary"), None) => Ok(ScoringPolicy::Binary),
+            (Some("linear"), None) => Ok(ScoringPolicy::Power(1.0)),
+            (Some("power"), Some(exponent_str)) => match exponent_str.parse::<f64>() {
+                Ok(exponent) if exponent.is_finite() && exponent > 0.0 => {
+                    Ok(ScoringPolicy::Power(exponent))
+                }
+                _ => bail!(
+                    "The scoring policy exponent '{}' is expected to be a positive number",
+                    exponent_str
+                ),
+            },
+            _ => bail!(
+                "The scoring policy '{}' is not supported (binary, linear or power:<exponent> are)",
+                s
+            ),
+        }
+    }
+}
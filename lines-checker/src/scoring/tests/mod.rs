@@ -0,0 +1,96 @@
+use std::fs;
+use std::path::PathBuf;
+
+use failure;
+
+use lines_game_engine::{GameScore, RuleSet};
+
+use super::super::testing::TemporaryDirectory;
+use super::super::{Config, SolutionOutput};
+use super::ScoringPolicy;
+
+#[test]
+fn scoring_policies_can_be_formatted_and_parsed() {
+    for &(policy_str, policy) in &[
+        ("binary", ScoringPolicy::Binary),
+        ("linear", ScoringPolicy::Power(1.0)),
+        ("power:2", ScoringPolicy::Power(2.0)),
+        ("power:0.5", ScoringPolicy::Power(0.5)),
+    ] {
+        assert_eq!(policy_str.parse::<ScoringPolicy>().unwrap(), policy);
+        assert_eq!(policy.to_string(), policy_str);
+    }
+    assert_eq!(ScoringPolicy::default(), ScoringPolicy::Binary);
+}
+
+#[test]
+fn invalid_scoring_policies_are_rejected() {
+    for policy_str in &[
+        "",
+        "quadratic",
+        "linear:2",
+        "power",
+        "power:",
+        "power:0",
+        "power:-1",
+        "power:nan",
+        "power:inf",
+        "power:x",
+    ] {
+        assert!(
+            policy_str.parse::<ScoringPolicy>().is_err(),
+            "{}",
+            policy_str
+        );
+    }
+}
+
+#[test]
+fn points_follow_the_scoring_curve() {
+    let points = |policy: ScoringPolicy, game_score: u32, expected_game_score: u32| {
+        policy.points(GameScore::new(game_score), GameScore::new(expected_game_score))
+    };
+    assert_eq!(points(ScoringPolicy::Binary, 4, 10), None);
+    assert_eq!(points(ScoringPolicy::Power(1.0), 4, 10), Some(0.4));
+    assert_eq!(points(ScoringPolicy::Power(1.0), 0, 10), Some(0.0));
+    assert!((points(ScoringPolicy::Power(2.0), 4, 10).unwrap() - 0.16).abs() < 1e-12);
+    assert!((points(ScoringPolicy::Power(0.5), 4, 16).unwrap() - 0.5).abs() < 1e-12);
+    // The points never go beyond the full credit.
+    assert_eq!(points(ScoringPolicy::Power(1.0), 15, 10), Some(1.0));
+    assert_eq!(points(ScoringPolicy::Power(0.5), 3, 0), Some(1.0));
+}
+
+fn expected_game_score(test_answer: &str) -> Result<(GameScore, ScoringPolicy), failure::Error> {
+    let temporary_directory = TemporaryDirectory::new("scoring-answer");
+    let test_answer_filepath = temporary_directory.join("answer.txt");
+    fs::write(&test_answer_filepath, test_answer).unwrap();
+    Config {
+        test_input_filepath: PathBuf::new(),
+        test_answer_filepath,
+        solution_output: SolutionOutput::Stdin,
+        rules: RuleSet::default(),
+    }.expected_game_score()
+}
+
+#[test]
+fn answer_file_sets_expected_score_and_scoring_policy() {
+    assert_eq!(
+        expected_game_score("120 power:2\n").unwrap(),
+        (GameScore::new(120), ScoringPolicy::Power(2.0))
+    );
+    assert_eq!(
+        expected_game_score("120\n").unwrap(),
+        (GameScore::new(120), ScoringPolicy::Binary)
+    );
+    assert_eq!(
+        expected_game_score("").unwrap(),
+        (GameScore::new(0), ScoringPolicy::Binary)
+    );
+}
+
+#[test]
+fn answer_file_with_garbage_is_rejected() {
+    for test_answer in &["120 linear extra", "120 square", "many"] {
+        assert!(expected_game_score(test_answer).is_err(), "{}", test_answer);
+    }
+}
@@ -0,0 +1,214 @@
+//! Runs the checker binary the way the judging systems do.
+
+use std::fs;
+use std::process::Command;
+
+extern crate lines_game_engine;
+use lines_game_engine::GameBoard;
+#[macro_use]
+extern crate serde_json;
+
+#[path = "../src/testing/mod.rs"]
+mod testing;
+use testing::TemporaryDirectory;
+
+const FAIL_EXIT_CODE: i32 = 3;
+
+/// A valid test input: an empty board and its score.
+fn test_input() -> String {
+    format!("{}\n0\n", GameBoard::default())
+}
+
+struct JuryFailureCase {
+    name: &'static str,
+    /// The test input and the test answer; a missing file is `None`.
+    test_input: Option<String>,
+    test_answer: Option<&'static str>,
+    rule_set: Option<&'static str>,
+    /// The number of the input, output and answer paths given to the checker, and the extra
+    /// arguments after them.
+    paths_count: usize,
+    extra_args: &'static [&'static str],
+    expected_message: &'static str,
+}
+
+impl Default for JuryFailureCase {
+    fn default() -> Self {
+        Self {
+            name: "",
+            test_input: Some(test_input()),
+            test_answer: Some("0"),
+            rule_set: None,
+            paths_count: 3,
+            extra_args: &[],
+            expected_message: "",
+        }
+    }
+}
+
+/// The broken jury files and arguments must be judged as Fail with its exit code rather than as
+/// a verdict about the solution.
+#[test]
+fn broken_jury_files_and_arguments_fail_the_check() {
+    let cases = vec![
+        JuryFailureCase {
+            name: "missing test input",
+            test_input: None,
+            expected_message: "The test input file could not be read",
+            ..Default::default()
+        },
+        JuryFailureCase {
+            name: "missing test answer",
+            test_answer: None,
+            expected_message: "The test answer file could not be read",
+            ..Default::default()
+        },
+        JuryFailureCase {
+            name: "malformed board",
+            test_input: Some(test_input().replacen("_", "X", 1)),
+            expected_message: "The game board in the test input could not be parsed",
+            ..Default::default()
+        },
+        JuryFailureCase {
+            name: "missing score",
+            test_input: Some(GameBoard::default().to_string()),
+            expected_message: "The game score is missing in the test input",
+            ..Default::default()
+        },
+        JuryFailureCase {
+            name: "malformed score",
+            test_input: Some(format!("{}\nten\n", GameBoard::default())),
+            expected_message: "The game score in the test input could not be parsed",
+            ..Default::default()
+        },
+        JuryFailureCase {
+            name: "malformed game over line",
+            test_input: Some(format!("{}\n0\nGAME OVER bored\n", GameBoard::default())),
+            expected_message: "The game over reason in the test input could not be parsed",
+            ..Default::default()
+        },
+        JuryFailureCase {
+            name: "malformed expected score",
+            test_answer: Some("ten"),
+            expected_message: "The test answer file should be empty or contain an integer",
+            ..Default::default()
+        },
+        JuryFailureCase {
+            name: "malformed scoring policy",
+            test_answer: Some("10 quadratic"),
+            expected_message: "The scoring policy in the test answer file is invalid",
+            ..Default::default()
+        },
+        JuryFailureCase {
+            name: "invalid rule set",
+            rule_set: Some("line_size=0"),
+            expected_message: "RULE_SET environment variable is not a valid rule set",
+            ..Default::default()
+        },
+        JuryFailureCase {
+            name: "no arguments",
+            paths_count: 0,
+            expected_message: "The first argument should be a path to the test input file",
+            ..Default::default()
+        },
+        JuryFailureCase {
+            name: "missing answer argument",
+            paths_count: 2,
+            expected_message: "The third argument should be a path to the test answer file",
+            ..Default::default()
+        },
+        JuryFailureCase {
+            name: "extra argument",
+            extra_args: &["result.txt"],
+            expected_message: "Unexpected checker argument 'result.txt'",
+            ..Default::default()
+        },
+    ];
+    for case in cases {
+        let temporary_directory = TemporaryDirectory::new("jury-failure");
+        let test_input_filepath = temporary_directory.join("input.txt");
+        let solution_output_filepath = temporary_directory.join("output.txt");
+        let test_answer_filepath = temporary_directory.join("answer.txt");
+        if let Some(ref test_input) = case.test_input {
+            fs::write(&test_input_filepath, test_input).unwrap();
+        }
+        if let Some(test_answer) = case.test_answer {
+            fs::write(&test_answer_filepath, test_answer).unwrap();
+        }
+        fs::write(&solution_output_filepath, "1 1 2 2").unwrap();
+
+        let mut checker = Command::new(env!("CARGO_BIN_EXE_lines-checker"));
+        checker
+            .args(
+                [
+                    &test_input_filepath,
+                    &solution_output_filepath,
+                    &test_answer_filepath,
+                ].iter()
+                    .take(case.paths_count),
+            )
+            .args(case.extra_args)
+            .env_remove("CHECKER_MODE")
+            .env_remove("CHECKER_REPORT")
+            .env_remove("RULE_SET");
+        if let Some(rule_set) = case.rule_set {
+            checker.env("RULE_SET", rule_set);
+        }
+        let output = checker.output().unwrap();
+        let log = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(FAIL_EXIT_CODE), "{}: {}", case.name, log);
+        assert!(
+            log.contains("Fail (judge error) due to") && log.contains(case.expected_message),
+            "{}: {}",
+            case.name,
+            log
+        );
+    }
+}
+
+#[test]
+fn report_has_the_game_error_of_the_rejected_output() {
+    let temporary_directory = TemporaryDirectory::new("checker-report");
+    let test_input_filepath = temporary_directory.join("input.txt");
+    let solution_output_filepath = temporary_directory.join("output.txt");
+    let test_answer_filepath = temporary_directory.join("answer.txt");
+    let checker_report_filepath = temporary_directory.join("report.json");
+    fs::write(&test_input_filepath, test_input()).unwrap();
+    fs::write(&test_answer_filepath, "0").unwrap();
+    for &(solution_output, exit_code, ref expected_failure_reason) in &[
+        (
+            "1 1 2 2",
+            1,
+            json!({
+                "kind": "invalid_move",
+                "error": {"kind": "no_ball_at_source", "x": 1, "y": 1},
+            }),
+        ),
+        (
+            "1 1 2 0",
+            2,
+            json!({
+                "kind": "invalid_output_format",
+                "message": "The game board move is invalid: The location (2, 0) is out of the \
+                            9x9 board.",
+                "error": {"kind": "out_of_board", "x": 2, "y": 0, "board_size": 9},
+            }),
+        ),
+    ] {
+        fs::write(&solution_output_filepath, solution_output).unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_lines-checker"))
+            .arg(&test_input_filepath)
+            .arg(&solution_output_filepath)
+            .arg(&test_answer_filepath)
+            .env_remove("CHECKER_MODE")
+            .env_remove("RULE_SET")
+            .env("CHECKER_REPORT", &checker_report_filepath)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(exit_code), "{}", solution_output);
+
+        let report: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&checker_report_filepath).unwrap()).unwrap();
+        assert_eq!(&report["failure_reason"], expected_failure_reason, "{}", solution_output);
+    }
+}
@@ -0,0 +1,111 @@
+#[macro_use]
+extern crate criterion;
+#[macro_use]
+extern crate failure;
+extern crate lines_game_engine;
+
+use criterion::{BatchSize, Criterion};
+
+use lines_game_engine::{GameBoard, GameBoardMove};
+
+mod previous_game_board;
+
+const MID_GAME_BOARD: &str = "\
+R _ G _ _ B _ _ Y
+_ R _ _ C _ _ P _
+M _ R _ _ _ G _ _
+_ _ _ R B _ _ _ C
+Y Y _ _ _ _ M _ _
+_ _ P _ G _ _ B _
+C _ _ _ _ Y _ _ R
+_ B _ M _ _ C _ _
+G _ _ _ P _ _ R _
+";
+
+const CROWDED_GAME_BOARD: &str = "\
+R G B Y M P C R G
+B Y M P C R G B Y
+M P C R _ _ Y M P
+C R G B _ _ M P C
+G B Y M P C R G B
+Y M P C R G B Y M
+P C R G B Y M P C
+R G B Y _ P C R G
+B Y M P _ R G B Y
+";
+
+fn bench_move_ball(c: &mut Criterion) {
+    let mut group = c.benchmark_group("move_ball");
+    for &(name, board_str, (from_x, from_y, to_x, to_y)) in &[
+        ("mid_game", MID_GAME_BOARD, (9, 1, 5, 5)),
+        ("line_clear", MID_GAME_BOARD, (5, 4, 5, 5)),
+        ("crowded", CROWDED_GAME_BOARD, (5, 2, 6, 4)),
+    ] {
+        let game_board = board_str.parse::<GameBoard>().unwrap();
+        let game_move = format!("{} {} {} {}", from_x, from_y, to_x, to_y)
+            .parse::<GameBoardMove>()
+            .unwrap();
+        group.bench_function(format!("bitboard/{}", name), |b| {
+            b.iter_batched(
+                || game_board.clone(),
+                |mut game_board| game_board.move_ball(&game_move).ok(),
+                BatchSize::SmallInput,
+            )
+        });
+        let previous_game_board = board_str.parse::<previous_game_board::GameBoard>().unwrap();
+        let previous_game_move = previous_game_board::GameBoardMove {
+            from: previous_game_board::GameBoardLocation::from_coords(from_x, from_y).unwrap(),
+            to: previous_game_board::GameBoardLocation::from_coords(to_x, to_y).unwrap(),
+        };
+        group.bench_function(format!("previous/{}", name), |b| {
+            b.iter_batched(
+                || previous_game_board.clone(),
+                |mut previous_game_board| previous_game_board.move_ball(&previous_game_move).ok(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_connected_zones(c: &mut Criterion) {
+    let mut group = c.benchmark_group("connected_zones");
+    for &(name, board_str) in &[("mid_game", MID_GAME_BOARD), ("crowded", CROWDED_GAME_BOARD)] {
+        let game_board = board_str.parse::<GameBoard>().unwrap();
+        group.bench_function(format!("bitboard/{}", name), |b| {
+            b.iter(|| game_board.connected_zones())
+        });
+        let previous_game_board = board_str.parse::<previous_game_board::GameBoard>().unwrap();
+        group.bench_function(format!("previous/{}", name), |b| {
+            b.iter(|| previous_game_board.connected_zones())
+        });
+    }
+    group.finish();
+}
+
+fn bench_legal_moves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("legal_moves");
+    for &(name, board_str) in &[("mid_game", MID_GAME_BOARD), ("crowded", CROWDED_GAME_BOARD)] {
+        let game_board = board_str.parse::<GameBoard>().unwrap();
+        let previous_game_board = board_str.parse::<previous_game_board::GameBoard>().unwrap();
+        assert_eq!(
+            game_board.legal_moves().count(),
+            previous_game_board.legal_moves().count()
+        );
+        group.bench_function(format!("bitboard/{}", name), |b| {
+            b.iter(|| game_board.legal_moves())
+        });
+        group.bench_function(format!("previous/{}", name), |b| {
+            b.iter(|| previous_game_board.legal_moves())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_move_ball,
+    bench_connected_zones,
+    bench_legal_moves
+);
+criterion_main!(benches);
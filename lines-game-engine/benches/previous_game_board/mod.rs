@@ -0,0 +1,502 @@
+//! The `GameBoard` as it was before the bit boards, with the locations and the move outcomes it
+//! used, kept to compare the two implementations. Only the code the benchmarks run is copied (and
+//! the board is made `Clone` for the benchmark setup); the board cells, colors, rule sets and line
+//! directions are the engine ones, since they have not changed.
+
+use std::iter;
+use std::marker::PhantomData;
+use std::mem;
+use std::str;
+use std::vec;
+
+use failure;
+
+use lines_game_engine::{BallColor, GameBoardCell, GameScore, LineDirection, RuleSet,
+                        GAME_BOARD_SIZE};
+
+pub type GameBoard<'a> = SizedGameBoard<'a, GAME_BOARD_SIZE>;
+pub type GameBoardLocation = SizedGameBoardLocation<GAME_BOARD_SIZE>;
+pub type GameBoardMove = SizedGameBoardMove<GAME_BOARD_SIZE>;
+
+pub type GameBoardNeighbours<const N: usize> = iter::Chain<
+    iter::Chain<
+        iter::Chain<
+            iter::Take<iter::Skip<SizedGameBoardWalker<N>>>,
+            iter::Take<iter::Skip<SizedGameBoardWalker<N>>>,
+        >,
+        iter::Take<iter::Skip<SizedGameBoardWalker<N>>>,
+    >,
+    iter::Take<iter::Skip<SizedGameBoardWalker<N>>>,
+>;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SizedGameBoardLocation<const N: usize> {
+    x_offset: usize,
+    y_offset: usize,
+}
+
+impl<const N: usize> SizedGameBoardLocation<N> {
+    #[inline]
+    pub fn from_offsets(x_offset: usize, y_offset: usize) -> Result<Self, failure::Error> {
+        ensure!(
+            x_offset < N,
+            "X offset ({}) should be less than the board size ({}).",
+            x_offset,
+            N
+        );
+        ensure!(
+            y_offset < N,
+            "Y offset ({}) should be less than the board size ({}).",
+            y_offset,
+            N
+        );
+        Ok(Self { x_offset, y_offset })
+    }
+
+    #[inline]
+    pub fn from_coords(x: usize, y: usize) -> Result<Self, failure::Error> {
+        ensure!(
+            x > 0 && x <= N,
+            "X ({}) should be in the range from 1 to {} (the board size).",
+            x,
+            N
+        );
+        ensure!(
+            y > 0 && y <= N,
+            "Y ({}) should be in the range from 1 to {} (the board size).",
+            y,
+            N
+        );
+        Self::from_offsets(x - 1, y - 1)
+    }
+
+    #[inline]
+    pub fn zero() -> Self {
+        Self {
+            x_offset: 0,
+            y_offset: 0,
+        }
+    }
+
+    #[inline]
+    pub fn x_offset(&self) -> usize {
+        self.x_offset
+    }
+
+    #[inline]
+    pub fn y_offset(&self) -> usize {
+        self.y_offset
+    }
+
+    pub fn update(&mut self, dx: isize, dy: isize) -> Result<(), failure::Error> {
+        let dx_u: usize;
+        let dy_u: usize;
+        if dx > 0 {
+            dx_u = dx as usize;
+            ensure!(
+                dx_u < N,
+                "dx ({}) should be less than the board size ({})",
+                dx_u,
+                N
+            );
+            ensure!(
+                N - dx_u > self.x_offset,
+                "We refuse to move (X ({}) + dx ({}) would be out of the board ({}))",
+                self.x_offset,
+                dx_u,
+                N
+            );
+            self.x_offset += dx_u;
+        } else if dx < 0 {
+            dx_u = (-dx) as usize;
+            ensure!(
+                dx_u < N,
+                "dx (-{}) should be less than the board size ({})",
+                dx_u,
+                N
+            );
+            ensure!(
+                dx_u <= self.x_offset,
+                "We refuse to move (X ({}) - dx ({}) would be out of the board ({}))",
+                self.x_offset,
+                dx_u,
+                N
+            );
+            self.x_offset -= dx_u;
+        }
+        if dy > 0 {
+            dy_u = dy as usize;
+            ensure!(
+                dy_u < N,
+                "dy ({}) should be less than the board size ({})",
+                dy_u,
+                N
+            );
+            ensure!(
+                N - dy_u > self.y_offset,
+                "We refuse to move (Y ({}) + dy ({}) would be out of the board ({}))",
+                self.y_offset,
+                dy_u,
+                N
+            );
+            self.y_offset += dy_u;
+        } else if dy < 0 {
+            dy_u = (-dy) as usize;
+            ensure!(
+                dy_u < N,
+                "dy (-{}) should be less than the board size ({})",
+                dy_u,
+                N
+            );
+            ensure!(
+                dy_u <= self.y_offset,
+                "We refuse to move (Y ({}) - dy ({}) would be out of the board ({}))",
+                self.y_offset,
+                dy_u,
+                N
+            );
+            self.y_offset -= dy_u;
+        }
+        Ok(())
+    }
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        Self::zero()
+            .walk(0, 1)
+            .flat_map(|line_location| line_location.walk(1, 0))
+    }
+
+    pub fn walk(&self, dx: isize, dy: isize) -> SizedGameBoardWalker<N> {
+        SizedGameBoardWalker::new(*self, dx, dy)
+    }
+
+    pub fn neighbours(&self) -> GameBoardNeighbours<N> {
+        self.walk(-1, 0)
+            .skip(1)
+            .take(1)
+            .chain(self.walk(1, 0).skip(1).take(1))
+            .chain(self.walk(0, -1).skip(1).take(1))
+            .chain(self.walk(0, 1).skip(1).take(1))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SizedGameBoardMove<const N: usize> {
+    pub from: SizedGameBoardLocation<N>,
+    pub to: SizedGameBoardLocation<N>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct SizedGameBoardWalker<const N: usize> {
+    next_location: Option<SizedGameBoardLocation<N>>,
+    dx: isize,
+    dy: isize,
+}
+
+impl<const N: usize> SizedGameBoardWalker<N> {
+    fn new(start_location: SizedGameBoardLocation<N>, dx: isize, dy: isize) -> Self {
+        Self {
+            next_location: Some(start_location),
+            dx,
+            dy,
+        }
+    }
+}
+
+impl<const N: usize> Iterator for SizedGameBoardWalker<N> {
+    type Item = SizedGameBoardLocation<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current_location = self.next_location;
+        let mut is_edge_reached = false;
+        if let Some(ref mut next_location) = self.next_location {
+            is_edge_reached = next_location.update(self.dx, self.dy).is_err();
+        }
+        if is_edge_reached {
+            self.next_location = None;
+        }
+        current_location
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SizedClearedLine<const N: usize> {
+    pub direction: LineDirection,
+    pub start: SizedGameBoardLocation<N>,
+    pub length: usize,
+    pub ball_color: BallColor,
+}
+
+impl<const N: usize> SizedClearedLine<N> {
+    pub fn locations(&self) -> impl Iterator<Item = SizedGameBoardLocation<N>> {
+        let (dx, dy) = self.direction.delta();
+        self.start.walk(dx, dy).take(self.length)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizedMoveOutcome<const N: usize> {
+    pub cleared_lines: Vec<SizedClearedLine<N>>,
+    pub removed_locations: Vec<SizedGameBoardLocation<N>>,
+    pub score: GameScore,
+}
+
+impl<const N: usize> SizedMoveOutcome<N> {
+    /// The number of balls the score is computed from; a ball shared by intersecting lines is
+    /// counted once per line.
+    pub fn dropped_balls_count(&self) -> usize {
+        self.cleared_lines
+            .iter()
+            .map(|cleared_line| cleared_line.length)
+            .sum()
+    }
+}
+
+impl<const N: usize> Default for SizedMoveOutcome<N> {
+    fn default() -> Self {
+        Self {
+            cleared_lines: Vec::new(),
+            removed_locations: Vec::new(),
+            score: GameScore::new(0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SizedGameBoard<'a, const N: usize> {
+    board: [[GameBoardCell; N]; N],
+    rules: RuleSet,
+    phantom: PhantomData<&'a i32>,
+}
+
+impl<'a, const N: usize> SizedGameBoard<'a, N> {
+    pub fn at(&'a self, location: &SizedGameBoardLocation<N>) -> &'a GameBoardCell {
+        &self.board[location.y_offset()][location.x_offset()]
+    }
+
+    pub fn move_ball(
+        &mut self,
+        game_move: &SizedGameBoardMove<N>,
+    ) -> Result<SizedMoveOutcome<N>, failure::Error> {
+        ensure!(
+            !self.at(&game_move.from).is_empty(),
+            "There is no ball in {:?}",
+            game_move.from
+        );
+        ensure!(
+            self.at(&game_move.to).is_empty(),
+            "The place {:?} is already taken by another ball.",
+            game_move.to
+        );
+        let game_board_zones = self.connected_zones();
+        debug_assert!(
+            game_board_zones.at(&game_move.from).is_none(),
+            "Sanity check failed! The 'from' location should not belong to any zone as it is taken by a ball."
+        );
+        debug_assert!(
+            game_board_zones.at(&game_move.to).is_some(),
+            "Sanity check failed! The 'to' location should belong to some zone."
+        );
+        let destination_zone_id = game_board_zones.at(&game_move.to);
+        ensure!(
+            game_move
+                .from
+                .neighbours()
+                .any(
+                    |neighbour_location| game_board_zones.at(&neighbour_location)
+                        == destination_zone_id
+                ),
+            "There is no way to move the ball."
+        );
+        self.board[game_move.to.y_offset()][game_move.to.x_offset()] = mem::replace(
+            &mut self.board[game_move.from.y_offset()][game_move.from.x_offset()],
+            GameBoardCell(None),
+        );
+        Ok(self.drop_lines_if_any(&game_move.to))
+    }
+
+    fn drop_lines_if_any(&mut self, location: &SizedGameBoardLocation<N>) -> SizedMoveOutcome<N> {
+        let mut move_outcome = SizedMoveOutcome::default();
+        let ball_color = match self.at(location).0 {
+            Some(ball_color) => ball_color,
+            None => return move_outcome,
+        };
+        let current_cell_copy = GameBoardCell(Some(ball_color));
+        for &direction in &LineDirection::ALL {
+            let (dx, dy) = direction.delta();
+            let west_balls_count = location
+                .walk(-dx, -dy)
+                .skip(1)
+                .take_while(|location| *self.at(location) == current_cell_copy)
+                .count();
+            let east_balls_count = location
+                .walk(dx, dy)
+                .skip(1)
+                .take_while(|location| *self.at(location) == current_cell_copy)
+                .count();
+            let line_size = west_balls_count + east_balls_count + 1;
+            if line_size >= self.rules.line_size() {
+                let mut line_start_location = *location;
+                line_start_location
+                    .update(
+                        -dx * (west_balls_count as isize),
+                        -dy * (west_balls_count as isize),
+                    )
+                    .expect("dx / dy manipulation should never fail.");
+                move_outcome.cleared_lines.push(SizedClearedLine {
+                    direction,
+                    start: line_start_location,
+                    length: line_size,
+                    ball_color,
+                });
+            }
+        }
+        for cleared_line in &move_outcome.cleared_lines {
+            for location in cleared_line.locations() {
+                if !move_outcome.removed_locations.contains(&location) {
+                    move_outcome.removed_locations.push(location);
+                }
+            }
+        }
+        for location in &move_outcome.removed_locations {
+            self.board[location.y_offset()][location.x_offset()] = GameBoardCell(None);
+        }
+        move_outcome.score = self.rules.score(move_outcome.dropped_balls_count());
+        move_outcome
+    }
+
+    pub fn connected_zones(&self) -> SizedGameBoardConnectedZones<N> {
+        SizedGameBoardConnectedZones::new(self)
+    }
+
+    pub fn legal_moves(&self) -> vec::IntoIter<SizedGameBoardMove<N>> {
+        let game_board_zones = self.connected_zones();
+        SizedGameBoardLocation::all()
+            .filter(|location| !self.at(location).is_empty())
+            .flat_map(|from| {
+                game_board_zones
+                    .reachable_from(&from)
+                    .into_iter()
+                    .map(move |to| SizedGameBoardMove { from, to })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<'a, const N: usize> Default for SizedGameBoard<'a, N> {
+    fn default() -> Self {
+        Self {
+            board: [[GameBoardCell(None); N]; N],
+            rules: RuleSet::default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, const N: usize> str::FromStr for SizedGameBoard<'a, N> {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut game_board = Self::default();
+        let mut lines_count = 0;
+        for (line, location) in s.lines().zip(SizedGameBoardLocation::<N>::zero().walk(0, 1)) {
+            let mut line_chars = line.chars();
+            for location in location.walk(1, 0) {
+                let cell_char = line_chars
+                    .next()
+                    .ok_or_else(|| format_err!("Unexpected end of line."))?;
+                game_board.board[location.y_offset()][location.x_offset()] =
+                    GameBoardCell::from_char(cell_char)?;
+                if location.x_offset() < N - 1 {
+                    let space_char = line_chars
+                        .next()
+                        .ok_or_else(|| format_err!("Unexpected end of line."))?;
+                    if !space_char.is_whitespace() {
+                        bail!("Whitespace was expected but '{:?}' found", space_char);
+                    }
+                }
+            }
+            lines_count += 1;
+        }
+        if lines_count != N {
+            bail!(
+                "There are not enough / too many lines ({}) in the game board string.",
+                lines_count
+            );
+        }
+        Ok(game_board)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConnectedZoneId(u8);
+
+pub type ConnectedZoneCell = Option<ConnectedZoneId>;
+
+#[derive(Debug)]
+pub struct SizedGameBoardConnectedZones<const N: usize>([[ConnectedZoneCell; N]; N]);
+
+impl<const N: usize> SizedGameBoardConnectedZones<N> {
+    pub fn new(game_board: &SizedGameBoard<N>) -> Self {
+        let mut connected_zones = SizedGameBoardConnectedZones([[None; N]; N]);
+        let mut zone_id = ConnectedZoneId(1);
+        for location in SizedGameBoardLocation::<N>::zero().walk(0, 1) {
+            for location in location.walk(1, 0) {
+                if connected_zones.0[location.y_offset()][location.x_offset()].is_none() {
+                    connected_zones.walk_and_mark(game_board, &location, zone_id);
+                    zone_id.0 += 1;
+                }
+            }
+        }
+        connected_zones
+    }
+
+    fn walk_and_mark(
+        &mut self,
+        game_board: &SizedGameBoard<N>,
+        current_location: &SizedGameBoardLocation<N>,
+        zone_id: ConnectedZoneId,
+    ) {
+        if let Some(current_location_zone_id) = self.at(current_location) {
+            assert_eq!(
+                current_location_zone_id,
+                zone_id,
+                "Each cell can only belong to a single connection zones."
+            );
+            return;
+        }
+        if !game_board.at(current_location).is_empty() {
+            return;
+        }
+        self.0[current_location.y_offset()][current_location.x_offset()] = Some(zone_id);
+        for neighbour_location in current_location.neighbours() {
+            self.walk_and_mark(game_board, &neighbour_location, zone_id);
+        }
+    }
+
+    #[inline]
+    pub fn at(&self, location: &SizedGameBoardLocation<N>) -> ConnectedZoneCell {
+        self.0[location.y_offset()][location.x_offset()]
+    }
+
+    /// Lists the empty cells that a ball at `location` can be moved to.
+    pub fn reachable_from(
+        &self,
+        location: &SizedGameBoardLocation<N>,
+    ) -> Vec<SizedGameBoardLocation<N>> {
+        let neighbour_zone_ids = location
+            .neighbours()
+            .filter_map(|neighbour_location| self.at(&neighbour_location))
+            .collect::<Vec<_>>();
+        if neighbour_zone_ids.is_empty() {
+            return Vec::new();
+        }
+        SizedGameBoardLocation::all()
+            .filter(|destination_location| {
+                self.at(destination_location)
+                    .is_some_and(|zone_id| neighbour_zone_ids.contains(&zone_id))
+            })
+            .collect()
+    }
+}
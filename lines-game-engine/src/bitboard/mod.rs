@@ -0,0 +1,266 @@
+use std::ops;
+
+#[cfg(test)]
+mod tests;
+
+const WORDS_COUNT: usize = 4;
+const WORD_BITS: usize = 64;
+
+/// The largest board size that fits into a [`BitBoard`] along with the padding column.
+pub const MAX_BOARD_SIZE: usize = 15;
+
+/// A set of board cells packed into bits.
+///
+/// Cell (x, y) of an N x N board is stored at bit `y * (N + 1) + x`, so every row is followed by
+/// an always-empty padding bit, which stops horizontal and diagonal shifts from wrapping over to
+/// the neighbouring row.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BitBoard([u64; WORDS_COUNT]);
+
+impl BitBoard {
+    pub const EMPTY: BitBoard = BitBoard([0; WORDS_COUNT]);
+
+    /// All the cells of an N x N board (without the padding bits).
+    #[inline]
+    pub fn cells<const N: usize>() -> Self {
+        BoardCells::<N>::CELLS
+    }
+
+    const fn compute_cells<const N: usize>() -> Self {
+        let mut cells = [0; WORDS_COUNT];
+        let mut y = 0;
+        while y < N {
+            let mut x = 0;
+            while x < N {
+                let index = Self::index_of::<N>(x, y);
+                cells[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+                x += 1;
+            }
+            y += 1;
+        }
+        BitBoard(cells)
+    }
+
+    #[inline]
+    pub const fn index_of<const N: usize>(x_offset: usize, y_offset: usize) -> usize {
+        y_offset * (N + 1) + x_offset
+    }
+
+    #[inline]
+    pub const fn offsets_of<const N: usize>(index: usize) -> (usize, usize) {
+        (index % (N + 1), index / (N + 1))
+    }
+
+    #[inline]
+    pub fn from_index(index: usize) -> Self {
+        let mut bit_board = Self::EMPTY;
+        bit_board.insert(index);
+        bit_board
+    }
+
+    /// All the bits with indices lower than `index`.
+    #[inline]
+    pub fn below(index: usize) -> Self {
+        let mut bit_board = Self::EMPTY;
+        for (word_index, word) in bit_board.0.iter_mut().enumerate() {
+            let word_start = word_index * WORD_BITS;
+            if index >= word_start + WORD_BITS {
+                *word = !0;
+            } else if index > word_start {
+                *word = !0 >> (WORD_BITS - (index - word_start));
+            }
+        }
+        bit_board
+    }
+
+    #[inline]
+    pub fn contains(&self, index: usize) -> bool {
+        self.0[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0
+    }
+
+    #[inline]
+    pub fn insert(&mut self, index: usize) {
+        self.0[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+    }
+
+    #[inline]
+    pub fn remove(&mut self, index: usize) {
+        self.0[index / WORD_BITS] &= !(1 << (index % WORD_BITS));
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    #[inline]
+    pub fn lowest_index(&self) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|&word| word != 0)
+            .map(|word_index| word_index * WORD_BITS + self.0[word_index].trailing_zeros() as usize)
+    }
+
+    #[inline]
+    pub fn highest_index(&self) -> Option<usize> {
+        self.0.iter().rposition(|&word| word != 0).map(|word_index| {
+            word_index * WORD_BITS + WORD_BITS - 1 - self.0[word_index].leading_zeros() as usize
+        })
+    }
+
+    /// Moves every bit by `delta` positions (towards the higher indices when positive); the bits
+    /// that leave the set are dropped.
+    #[inline]
+    pub fn shift(&self, delta: isize) -> Self {
+        if delta >= 0 {
+            self.shift_up(delta as usize)
+        } else {
+            self.shift_down((-delta) as usize)
+        }
+    }
+
+    fn shift_up(&self, bits: usize) -> Self {
+        debug_assert!(bits < WORD_BITS);
+        if bits == 0 {
+            return *self;
+        }
+        let mut shifted = [0; WORDS_COUNT];
+        for (word_index, shifted_word) in shifted.iter_mut().enumerate() {
+            *shifted_word = self.0[word_index] << bits;
+            if word_index > 0 {
+                *shifted_word |= self.0[word_index - 1] >> (WORD_BITS - bits);
+            }
+        }
+        BitBoard(shifted)
+    }
+
+    fn shift_down(&self, bits: usize) -> Self {
+        debug_assert!(bits < WORD_BITS);
+        if bits == 0 {
+            return *self;
+        }
+        let mut shifted = [0; WORDS_COUNT];
+        for (word_index, shifted_word) in shifted.iter_mut().enumerate() {
+            *shifted_word = self.0[word_index] >> bits;
+            if word_index + 1 < WORDS_COUNT {
+                *shifted_word |= self.0[word_index + 1] << (WORD_BITS - bits);
+            }
+        }
+        BitBoard(shifted)
+    }
+
+    /// Grows the set along `delta` (in both ways) while staying within `space`.
+    pub fn flood_along(&self, delta: isize, space: BitBoard) -> Self {
+        let mut flooded = *self & space;
+        loop {
+            let next_flooded = (flooded | flooded.shift(delta) | flooded.shift(-delta)) & space;
+            if next_flooded == flooded {
+                return flooded;
+            }
+            flooded = next_flooded;
+        }
+    }
+
+    /// Marks the cells that are 4-connected to the set within `space` on an N x N board.
+    pub fn flood<const N: usize>(&self, space: BitBoard) -> Self {
+        let mut flooded = *self & space;
+        loop {
+            let next_flooded = (flooded | flooded.neighbours::<N>()) & space;
+            if next_flooded == flooded {
+                return flooded;
+            }
+            flooded = next_flooded;
+        }
+    }
+
+    /// The 4-connected neighbours of the set on an N x N board (the set itself is not included
+    /// unless its cells neighbour each other).
+    #[inline]
+    pub fn neighbours<const N: usize>(&self) -> Self {
+        let row_delta = (N + 1) as isize;
+        (self.shift(1) | self.shift(-1) | self.shift(row_delta) | self.shift(-row_delta))
+            & Self::cells::<N>()
+    }
+
+    pub fn indices(&self) -> BitBoardIndices {
+        BitBoardIndices { bit_board: *self }
+    }
+}
+
+impl ops::BitAnd for BitBoard {
+    type Output = BitBoard;
+
+    #[inline]
+    fn bitand(mut self, other: BitBoard) -> BitBoard {
+        self &= other;
+        self
+    }
+}
+
+impl ops::BitAndAssign for BitBoard {
+    #[inline]
+    fn bitand_assign(&mut self, other: BitBoard) {
+        for (word, other_word) in self.0.iter_mut().zip(other.0.iter()) {
+            *word &= *other_word;
+        }
+    }
+}
+
+impl ops::BitOr for BitBoard {
+    type Output = BitBoard;
+
+    #[inline]
+    fn bitor(mut self, other: BitBoard) -> BitBoard {
+        self |= other;
+        self
+    }
+}
+
+impl ops::BitOrAssign for BitBoard {
+    #[inline]
+    fn bitor_assign(&mut self, other: BitBoard) {
+        for (word, other_word) in self.0.iter_mut().zip(other.0.iter()) {
+            *word |= *other_word;
+        }
+    }
+}
+
+impl ops::Not for BitBoard {
+    type Output = BitBoard;
+
+    #[inline]
+    fn not(self) -> BitBoard {
+        let mut inverted = self;
+        for word in &mut inverted.0 {
+            *word = !*word;
+        }
+        inverted
+    }
+}
+
+struct BoardCells<const N: usize>;
+
+impl<const N: usize> BoardCells<N> {
+    const CELLS: BitBoard = BitBoard::compute_cells::<N>();
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct BitBoardIndices {
+    bit_board: BitBoard,
+}
+
+impl Iterator for BitBoardIndices {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.bit_board.lowest_index()?;
+        self.bit_board.remove(index);
+        Some(index)
+    }
+}
@@ -0,0 +1,60 @@
+use super::BitBoard;
+
+#[test]
+fn bit_board_cells_skip_padding_column() {
+    let cells = BitBoard::cells::<9>();
+    assert_eq!(cells.count(), 81);
+    assert!(cells.contains(BitBoard::index_of::<9>(8, 0)));
+    assert!(!cells.contains(BitBoard::index_of::<9>(9, 0)));
+    assert_eq!(cells.lowest_index(), Some(0));
+    assert_eq!(cells.highest_index(), Some(BitBoard::index_of::<9>(8, 8)));
+    assert_eq!(BitBoard::cells::<15>().count(), 225);
+}
+
+#[test]
+fn bit_board_shifts_carry_across_words() {
+    let bit_board = BitBoard::from_index(63);
+    assert_eq!(bit_board.shift(1), BitBoard::from_index(64));
+    assert_eq!(bit_board.shift(17).shift(-17), bit_board);
+    assert_eq!(BitBoard::from_index(130).shift(-3), BitBoard::from_index(127));
+    assert!(BitBoard::from_index(2).shift(-3).is_empty());
+    assert!(BitBoard::from_index(255).shift(1).is_empty());
+}
+
+#[test]
+fn bit_board_neighbours_do_not_wrap_around_rows() {
+    let corner = BitBoard::from_index(BitBoard::index_of::<9>(8, 0));
+    assert_eq!(
+        corner.neighbours::<9>().indices().collect::<Vec<_>>(),
+        vec![
+            BitBoard::index_of::<9>(7, 0),
+            BitBoard::index_of::<9>(8, 1),
+        ]
+    );
+    let origin = BitBoard::from_index(0);
+    assert_eq!(origin.neighbours::<12>().count(), 2);
+}
+
+#[test]
+fn bit_board_flood_stays_within_space() {
+    let mut space = BitBoard::cells::<9>();
+    for y in 0..9 {
+        space.remove(BitBoard::index_of::<9>(4, y));
+    }
+    let left_half = BitBoard::from_index(0).flood::<9>(space);
+    assert_eq!(left_half.count(), 4 * 9);
+    assert!(!left_half.contains(BitBoard::index_of::<9>(5, 0)));
+
+    let row = BitBoard::from_index(BitBoard::index_of::<9>(1, 3)).flood_along(1, space);
+    assert_eq!(row.count(), 4);
+}
+
+#[test]
+fn bit_board_below_covers_lower_indices() {
+    assert!(BitBoard::below(0).is_empty());
+    assert_eq!(BitBoard::below(1), BitBoard::from_index(0));
+    assert_eq!(BitBoard::below(64).count(), 64);
+    assert_eq!(BitBoard::below(130).count(), 130);
+    assert_eq!(BitBoard::below(130).highest_index(), Some(129));
+    assert_eq!(BitBoard::below(256).count(), 256);
+}
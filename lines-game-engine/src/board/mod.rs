@@ -1,193 +1,504 @@
 use std::fmt;
-use std::marker::PhantomData;
-use std::mem;
+use std::hash::{Hash, Hasher};
 use std::str;
+use std::vec;
 
-use failure;
-
-use super::common::{BallColor, GameBoardCell, GameScore, GAME_BOARD_SIZE};
-use super::location::{GameBoardLocation, GameBoardMove};
+use super::bitboard::{BitBoard, MAX_BOARD_SIZE};
+use super::common::{BallColor, GameBoardCell, GAME_BOARD_SIZE};
+use super::error::GameError;
+use super::location::{SizedGameBoardLocation, SizedGameBoardMove};
+use super::outcome::{LineDirection, SizedClearedLine, SizedMoveOutcome};
+use super::rules::RuleSet;
+use super::zobrist::zobrist_key;
 
 #[cfg(test)]
 mod tests;
 
-#[derive(Debug)]
-pub struct GameBoard<'a> {
-    board: [[GameBoardCell; GAME_BOARD_SIZE]; GAME_BOARD_SIZE],
-    phantom: PhantomData<&'a i32>,
+pub type GameBoard = SizedGameBoard<GAME_BOARD_SIZE>;
+pub type GameBoardConnectedZones = SizedGameBoardConnectedZones<GAME_BOARD_SIZE>;
+
+/// The balls are kept as a bit board per color, which the line and path searches work on, and as
+/// an array of cells for the lookups by location.
+#[derive(Debug, Clone)]
+pub struct SizedGameBoard<const N: usize> {
+    balls: [BitBoard; 7],
+    occupied: BitBoard,
+    cells: [[GameBoardCell; N]; N],
+    zobrist_hash: u64,
+    rules: RuleSet,
 }
 
-impl<'a> GameBoard<'a> {
-    pub fn at(&'a self, location: &GameBoardLocation) -> &'a GameBoardCell {
-        &self.board[location.y_offset()][location.x_offset()]
+impl<const N: usize> SizedGameBoard<N> {
+    const SIZE_CHECK: () = assert!(
+        N > 0 && N <= MAX_BOARD_SIZE,
+        "The board size is not supported by the bitboard representation."
+    );
+
+    pub fn with_rules(rules: RuleSet) -> Result<Self, GameError> {
+        if rules.line_size() > N {
+            return Err(GameError::LineSizeExceedsBoard {
+                line_size: rules.line_size(),
+                board_size: N,
+            });
+        }
+        Ok(Self {
+            rules,
+            ..Self::default()
+        })
+    }
+
+    pub fn parse_with_rules(s: &str, rules: RuleSet) -> Result<Self, GameError> {
+        let mut game_board = Self::with_rules(rules)?;
+        let parsed_game_board = s.parse::<Self>()?;
+        game_board.balls = parsed_game_board.balls;
+        game_board.occupied = parsed_game_board.occupied;
+        game_board.cells = parsed_game_board.cells;
+        game_board.zobrist_hash = parsed_game_board.zobrist_hash;
+        for &ball_color in &BallColor::ALL {
+            if !rules.is_color_allowed(ball_color)
+                && !game_board.balls[ball_color.index()].is_empty()
+            {
+                return Err(GameError::ColorNotAllowed {
+                    ball_color,
+                    colors_count: rules.colors_count(),
+                });
+            }
+        }
+        Ok(game_board)
+    }
+
+    #[inline]
+    fn index_of(location: &SizedGameBoardLocation<N>) -> usize {
+        BitBoard::index_of::<N>(location.x_offset(), location.y_offset())
+    }
+
+    #[inline]
+    fn location_of(index: usize) -> SizedGameBoardLocation<N> {
+        let (x_offset, y_offset) = BitBoard::offsets_of::<N>(index);
+        SizedGameBoardLocation::from_offsets_unchecked(x_offset, y_offset)
+    }
+
+    fn set_cell(&mut self, location: &SizedGameBoardLocation<N>, cell: GameBoardCell) {
+        let index = Self::index_of(location);
+        if let Some(ball_color) = self.at(location).0 {
+            self.balls[ball_color.index()].remove(index);
+            self.occupied.remove(index);
+            self.zobrist_hash ^= zobrist_key(index, ball_color);
+        }
+        if let Some(ball_color) = cell.0 {
+            self.balls[ball_color.index()].insert(index);
+            self.occupied.insert(index);
+            self.zobrist_hash ^= zobrist_key(index, ball_color);
+        }
+        self.cells[location.y_offset()][location.x_offset()] = cell;
+    }
+
+    fn empty_cells(&self) -> BitBoard {
+        BitBoard::cells::<N>() & !self.occupied
+    }
+
+    fn reachable_cells(&self, location: &SizedGameBoardLocation<N>) -> BitBoard {
+        let empty_cells = self.empty_cells();
+        BitBoard::from_index(Self::index_of(location))
+            .neighbours::<N>()
+            .flood::<N>(empty_cells)
+    }
+
+    /// The Zobrist hash of the balls on the board; it is kept up to date on every change.
+    #[inline]
+    pub fn zobrist_hash(&self) -> u64 {
+        self.zobrist_hash
+    }
+
+    #[inline]
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    #[inline]
+    pub fn at(&self, location: &SizedGameBoardLocation<N>) -> &GameBoardCell {
+        &self.cells[location.y_offset()][location.x_offset()]
     }
 
     pub fn add_ball(
         &mut self,
-        location: &GameBoardLocation,
+        location: &SizedGameBoardLocation<N>,
         ball_color: BallColor,
-    ) -> Result<GameScore, failure::Error> {
-        ensure!(
-            self.at(location).is_empty(),
-            "The place {:?} is already taken by another ball.",
-            location
-        );
-        self.board[location.y_offset()][location.x_offset()] = GameBoardCell(Some(ball_color));
-        self.drop_lines_if_any(location)
-    }
-
-    pub fn move_ball(&mut self, game_move: &GameBoardMove) -> Result<GameScore, failure::Error> {
-        ensure!(
-            !self.at(&game_move.from).is_empty(),
-            "There is no ball in {:?}",
-            game_move.from
-        );
-        ensure!(
-            self.at(&game_move.to).is_empty(),
-            "The place {:?} is already taken by another ball.",
-            game_move.to
-        );
-        let game_board_zones = self.connected_zones();
-        debug_assert!(
-            game_board_zones.at(&game_move.from).is_none(),
-            "Sanity check failed! The 'from' location should not belong to any zone as it is taken by a ball."
-        );
-        debug_assert!(
-            game_board_zones.at(&game_move.to).is_some(),
-            "Sanity check failed! The 'to' location should belong to some zone."
-        );
-        let destination_zone_id = game_board_zones.at(&game_move.to);
-        ensure!(
-            game_move
-                .from
-                .neighbours()
-                .any(
-                    |neighbour_location| game_board_zones.at(&neighbour_location)
-                        == destination_zone_id
-                ),
-            "There is no way to move the ball."
-        );
-        self.board[game_move.to.y_offset()][game_move.to.x_offset()] = mem::replace(
-            &mut self.board[game_move.from.y_offset()][game_move.from.x_offset()],
-            GameBoardCell(None),
-        );
-        self.drop_lines_if_any(&game_move.to)
+    ) -> Result<SizedMoveOutcome<N>, GameError> {
+        if !self.rules.is_color_allowed(ball_color) {
+            return Err(GameError::ColorNotAllowed {
+                ball_color,
+                colors_count: self.rules.colors_count(),
+            });
+        }
+        self.ensure_empty(location)?;
+        self.set_cell(location, GameBoardCell(Some(ball_color)));
+        Ok(self.drop_lines_if_any(location))
+    }
+
+    pub fn move_ball(
+        &mut self,
+        game_move: &SizedGameBoardMove<N>,
+    ) -> Result<SizedMoveOutcome<N>, GameError> {
+        self.ensure_occupied(&game_move.from)?;
+        self.ensure_empty(&game_move.to)?;
+        if !self.reachable_cells(&game_move.from)
+            .contains(Self::index_of(&game_move.to))
+        {
+            return Err(GameError::NoPath {
+                from: (game_move.from.x(), game_move.from.y()),
+                to: (game_move.to.x(), game_move.to.y()),
+            });
+        }
+        let ball_cell = *self.at(&game_move.from);
+        self.set_cell(&game_move.from, GameBoardCell(None));
+        self.set_cell(&game_move.to, ball_cell);
+        Ok(self.drop_lines_if_any(&game_move.to))
+    }
+
+    /// Reverts `add_ball`; the board is left unchanged if it does not match the move outcome.
+    pub fn undo_add_ball(
+        &mut self,
+        location: &SizedGameBoardLocation<N>,
+        move_outcome: &SizedMoveOutcome<N>,
+    ) -> Result<(), GameError> {
+        self.ensure_cleared_lines_restorable(move_outcome)?;
+        if !Self::is_restored(location, move_outcome) {
+            self.ensure_occupied(location)?;
+        }
+        self.restore_cleared_lines(move_outcome);
+        self.set_cell(location, GameBoardCell(None));
+        Ok(())
     }
 
-    fn drop_lines_if_any(
+    /// Reverts `move_ball`; the board is left unchanged if it does not match the move outcome.
+    pub fn undo_move_ball(
         &mut self,
-        location: &GameBoardLocation,
-    ) -> Result<GameScore, failure::Error> {
-        let current_cell_copy = {
-            let current_cell = self.at(location);
-            if current_cell.is_empty() {
-                return Ok(GameScore::new(0));
+        game_move: &SizedGameBoardMove<N>,
+        move_outcome: &SizedMoveOutcome<N>,
+    ) -> Result<(), GameError> {
+        self.ensure_cleared_lines_restorable(move_outcome)?;
+        if !Self::is_restored(&game_move.to, move_outcome) {
+            self.ensure_occupied(&game_move.to)?;
+        }
+        self.ensure_empty(&game_move.from)?;
+        if Self::is_restored(&game_move.from, move_outcome) {
+            return Err(GameError::DestinationOccupied {
+                x: game_move.from.x(),
+                y: game_move.from.y(),
+            });
+        }
+        self.restore_cleared_lines(move_outcome);
+        let ball_cell = *self.at(&game_move.to);
+        self.set_cell(&game_move.to, GameBoardCell(None));
+        self.set_cell(&game_move.from, ball_cell);
+        Ok(())
+    }
+
+    fn ensure_empty(&self, location: &SizedGameBoardLocation<N>) -> Result<(), GameError> {
+        if !self.at(location).is_empty() {
+            return Err(GameError::DestinationOccupied {
+                x: location.x(),
+                y: location.y(),
+            });
+        }
+        Ok(())
+    }
+
+    fn ensure_occupied(&self, location: &SizedGameBoardLocation<N>) -> Result<(), GameError> {
+        if self.at(location).is_empty() {
+            return Err(GameError::NoBallAtSource {
+                x: location.x(),
+                y: location.y(),
+            });
+        }
+        Ok(())
+    }
+
+    fn ensure_cleared_lines_restorable(
+        &self,
+        move_outcome: &SizedMoveOutcome<N>,
+    ) -> Result<(), GameError> {
+        for cleared_line in &move_outcome.cleared_lines {
+            for location in cleared_line.locations() {
+                self.ensure_empty(&location)?;
             }
-            *current_cell
+        }
+        Ok(())
+    }
+
+    /// Whether the location gets a ball back when the cleared lines are restored.
+    fn is_restored(
+        location: &SizedGameBoardLocation<N>,
+        move_outcome: &SizedMoveOutcome<N>,
+    ) -> bool {
+        move_outcome.cleared_lines.iter().any(|cleared_line| {
+            cleared_line
+                .locations()
+                .any(|cleared_location| cleared_location == *location)
+        })
+    }
+
+    fn restore_cleared_lines(&mut self, move_outcome: &SizedMoveOutcome<N>) {
+        for cleared_line in &move_outcome.cleared_lines {
+            for location in cleared_line.locations() {
+                self.set_cell(&location, GameBoardCell(Some(cleared_line.ball_color)));
+            }
+        }
+    }
+
+    pub fn find_path(
+        &self,
+        game_move: &SizedGameBoardMove<N>,
+    ) -> Option<Vec<SizedGameBoardLocation<N>>> {
+        if self.at(&game_move.from).is_empty() || !self.at(&game_move.to).is_empty() {
+            return None;
+        }
+        let empty_cells = self.empty_cells();
+        let destination_index = Self::index_of(&game_move.to);
+        let mut visited_cells = BitBoard::from_index(Self::index_of(&game_move.from));
+        let mut distance_layers = vec![visited_cells];
+        while !visited_cells.contains(destination_index) {
+            let next_layer =
+                distance_layers[distance_layers.len() - 1].neighbours::<N>() & empty_cells
+                    & !visited_cells;
+            if next_layer.is_empty() {
+                return None;
+            }
+            visited_cells |= next_layer;
+            distance_layers.push(next_layer);
+        }
+        let mut path_cell = BitBoard::from_index(destination_index);
+        let mut path = vec![game_move.to];
+        for distance_layer in distance_layers.iter().rev().skip(1) {
+            let previous_index = (path_cell.neighbours::<N>() & *distance_layer)
+                .lowest_index()
+                .expect("Every distance layer should neighbour the next one.");
+            path_cell = BitBoard::from_index(previous_index);
+            path.push(Self::location_of(previous_index));
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    fn drop_lines_if_any(&mut self, location: &SizedGameBoardLocation<N>) -> SizedMoveOutcome<N> {
+        let mut move_outcome = SizedMoveOutcome::default();
+        let ball_color = match self.at(location).0 {
+            Some(ball_color) => ball_color,
+            None => return move_outcome,
         };
-        let mut number_of_dropped_balls = 0;
-        for &(dx, dy) in &[(1, 0), (0, 1), (1, 1), (1, -1)] {
-            let west_balls_count = location
-                .walk(-dx, -dy)
-                .skip(1)
-                .take_while(|location| *self.at(location) == current_cell_copy)
-                .count();
-            let east_balls_count = location
-                .walk(dx, dy)
-                .skip(1)
-                .take_while(|location| *self.at(location) == current_cell_copy)
-                .count();
-            let line_size = west_balls_count + east_balls_count + 1;
-            if line_size >= 5 {
-                number_of_dropped_balls += line_size;
-                let mut line_start_location = *location;
-                line_start_location
-                    .update(
-                        -dx * (west_balls_count as isize),
-                        -dy * (west_balls_count as isize),
-                    )
-                    .expect("dx / dy manipulation should never fail.");
-                for location in line_start_location.walk(dx, dy).take(line_size) {
-                    self.board[location.y_offset()][location.x_offset()] = GameBoardCell(None);
-                }
+        let ball_color_cells = self.balls[ball_color.index()];
+        let location_cell = BitBoard::from_index(Self::index_of(location));
+        let mut removed_cells = BitBoard::EMPTY;
+        for &direction in &LineDirection::ALL {
+            let (dx, dy) = direction.delta();
+            let index_delta = dy * (N as isize + 1) + dx;
+            let line_cells = location_cell.flood_along(index_delta, ball_color_cells);
+            let line_size = line_cells.count();
+            if line_size >= self.rules.line_size() {
+                let line_start_index = if index_delta > 0 {
+                    line_cells.lowest_index()
+                } else {
+                    line_cells.highest_index()
+                }.expect("The line should contain at least the current location.");
+                move_outcome.cleared_lines.push(SizedClearedLine {
+                    direction,
+                    start: Self::location_of(line_start_index),
+                    length: line_size,
+                    ball_color,
+                });
+                removed_cells |= line_cells;
             }
         }
-        Ok(GameScore::new(if number_of_dropped_balls < 5 {
-            0
-        } else {
-            (1 << (number_of_dropped_balls - 4)) + 8
-        }))
+        self.balls[ball_color.index()] &= !removed_cells;
+        self.occupied &= !removed_cells;
+        for removed_index in removed_cells.indices() {
+            self.zobrist_hash ^= zobrist_key(removed_index, ball_color);
+            let removed_location = Self::location_of(removed_index);
+            self.cells[removed_location.y_offset()][removed_location.x_offset()] =
+                GameBoardCell(None);
+            move_outcome.removed_locations.push(removed_location);
+        }
+        move_outcome.score = self.rules.score(move_outcome.dropped_balls_count());
+        move_outcome
     }
 
     pub fn is_empty(&self) -> bool {
-        self.board
-            .iter()
-            .all(|line| line.iter().all(|cell| cell.is_empty()))
+        self.occupied.is_empty()
     }
 
     pub fn is_full(&self) -> bool {
-        self.board
-            .iter()
-            .all(|line| line.iter().all(|cell| !cell.is_empty()))
+        self.occupied == BitBoard::cells::<N>()
+    }
+
+    pub fn balls_count(&self) -> usize {
+        self.occupied.count()
+    }
+
+    /// The empty locations in the row-major order.
+    pub fn empty_locations(&self) -> impl Iterator<Item = SizedGameBoardLocation<N>> {
+        self.empty_cells().indices().map(Self::location_of)
+    }
+
+    pub fn connected_zones(&self) -> SizedGameBoardConnectedZones<N> {
+        SizedGameBoardConnectedZones::new(self)
     }
 
-    pub fn connected_zones(&self) -> GameBoardConnectedZones {
-        GameBoardConnectedZones::new(self)
+    pub fn reachable_from(
+        &self,
+        location: &SizedGameBoardLocation<N>,
+    ) -> Vec<SizedGameBoardLocation<N>> {
+        if self.at(location).is_empty() {
+            return Vec::new();
+        }
+        self.reachable_cells(location)
+            .indices()
+            .map(Self::location_of)
+            .collect()
+    }
+
+    /// Lists every move in the order of the balls and then of their destinations (both
+    /// row-major). The destinations of a ball are the cells of the empty zones next to it, so the
+    /// zones are flooded once and their cells are listed once for all the balls.
+    pub fn legal_moves(&self) -> vec::IntoIter<SizedGameBoardMove<N>> {
+        let mut zones = Vec::new();
+        let mut legal_moves_count = 0;
+        let mut unmarked_cells = self.empty_cells();
+        while let Some(index) = unmarked_cells.lowest_index() {
+            let zone_cells = BitBoard::from_index(index).flood::<N>(unmarked_cells);
+            unmarked_cells &= !zone_cells;
+            let zone_balls = zone_cells.neighbours::<N>() & self.occupied;
+            let zone_locations = zone_cells
+                .indices()
+                .map(Self::location_of)
+                .collect::<Vec<_>>();
+            legal_moves_count += zone_balls.count() * zone_locations.len();
+            zones.push((zone_cells, zone_balls, zone_locations));
+        }
+        let mut legal_moves = Vec::with_capacity(legal_moves_count);
+        for from_index in self.occupied.indices() {
+            let from = Self::location_of(from_index);
+            let mut ball_zones = zones
+                .iter()
+                .filter(|&&(_, zone_balls, _)| zone_balls.contains(from_index));
+            let (first_zone_cells, _, first_zone_locations) = match ball_zones.next() {
+                Some(zone) => zone,
+                None => continue,
+            };
+            let mut reachable_cells = *first_zone_cells;
+            let mut is_single_zone = true;
+            for &(zone_cells, _, _) in ball_zones {
+                reachable_cells |= zone_cells;
+                is_single_zone = false;
+            }
+            if is_single_zone {
+                legal_moves.extend(
+                    first_zone_locations
+                        .iter()
+                        .map(|&to| SizedGameBoardMove { from, to }),
+                );
+            } else {
+                legal_moves.extend(reachable_cells.indices().map(|to_index| SizedGameBoardMove {
+                    from,
+                    to: Self::location_of(to_index),
+                }));
+            }
+        }
+        legal_moves.into_iter()
     }
 }
 
-impl<'a> Default for GameBoard<'a> {
+impl<const N: usize> Default for SizedGameBoard<N> {
     fn default() -> Self {
+        let () = Self::SIZE_CHECK;
         Self {
-            board: [[GameBoardCell(None); GAME_BOARD_SIZE]; GAME_BOARD_SIZE],
-            phantom: PhantomData,
+            balls: [BitBoard::EMPTY; 7],
+            occupied: BitBoard::EMPTY,
+            cells: [[GameBoardCell(None); N]; N],
+            zobrist_hash: 0,
+            rules: RuleSet::default(),
         }
     }
 }
 
-impl<'a> fmt::Display for GameBoard<'a> {
+impl<const N: usize> PartialEq for SizedGameBoard<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.zobrist_hash == other.zobrist_hash
+            && self.occupied == other.occupied
+            && self.balls == other.balls
+            && self.rules == other.rules
+    }
+}
+
+impl<const N: usize> Eq for SizedGameBoard<N> {}
+
+impl<const N: usize> Hash for SizedGameBoard<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.zobrist_hash.hash(state);
+    }
+}
+
+impl<const N: usize> fmt::Display for SizedGameBoard<N> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        for line in &self.board {
-            write!(fmt, "{}", line[0])?;
-            for cell in line.iter().skip(1) {
-                write!(fmt, " {}", cell)?;
+        for line_location in SizedGameBoardLocation::<N>::zero().walk(0, 1) {
+            write!(fmt, "{}", self.at(&line_location))?;
+            for location in line_location.walk(1, 0).skip(1) {
+                write!(fmt, " {}", self.at(&location))?;
             }
-            write!(fmt, "\n")?;
+            writeln!(fmt)?;
         }
         Ok(())
     }
 }
 
-impl<'a> str::FromStr for GameBoard<'a> {
-    type Err = failure::Error;
+impl<const N: usize> str::FromStr for SizedGameBoard<N> {
+    type Err = GameError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut game_board = Self::default();
         let mut lines_count = 0;
-        for (line, location) in s.lines().zip(GameBoardLocation::zero().walk(0, 1)) {
-            let mut line_chars = line.chars();
+        for (line, location) in s.lines().zip(SizedGameBoardLocation::<N>::zero().walk(0, 1)) {
+            let line_number = location.y();
+            let mut line_chars = line.chars().zip(1..);
             for location in location.walk(1, 0) {
-                let cell_char = line_chars
-                    .next()
-                    .ok_or_else(|| format_err!("Unexpected end of line."))?;
-                game_board.board[location.y_offset()][location.x_offset()] =
-                    GameBoardCell::from_char(cell_char)?;
-                if location.x_offset() < GAME_BOARD_SIZE - 1 {
-                    let space_char = line_chars
-                        .next()
-                        .ok_or_else(|| format_err!("Unexpected end of line."))?;
+                let (cell_char, column) = line_chars.next().ok_or_else(|| {
+                    GameError::parse_error(
+                        line_number,
+                        line.chars().count() + 1,
+                        "Unexpected end of line.",
+                    )
+                })?;
+                let cell = GameBoardCell::from_char(cell_char)
+                    .map_err(|why| GameError::parse_error(line_number, column, why))?;
+                game_board.set_cell(&location, cell);
+                if location.x_offset() < N - 1 {
+                    let (space_char, column) = line_chars.next().ok_or_else(|| {
+                        GameError::parse_error(
+                            line_number,
+                            line.chars().count() + 1,
+                            "Unexpected end of line.",
+                        )
+                    })?;
                     if !space_char.is_whitespace() {
-                        bail!("Whitespace was expected but '{:?}' found", space_char);
+                        return Err(GameError::parse_error(
+                            line_number,
+                            column,
+                            format!("Whitespace was expected but '{:?}' found", space_char),
+                        ));
                     }
                 }
             }
             lines_count += 1;
         }
-        if lines_count != GAME_BOARD_SIZE {
-            bail!(
-                "There are not enough / too many lines ({}) in the game board string.",
-                lines_count
-            );
+        if lines_count != N {
+            return Err(GameError::parse_error(
+                lines_count + 1,
+                1,
+                format!(
+                    "There are not enough / too many lines ({}) in the game board string.",
+                    lines_count
+                ),
+            ));
         }
         Ok(game_board)
     }
@@ -199,49 +510,57 @@ pub struct ConnectedZoneId(u8);
 pub type ConnectedZoneCell = Option<ConnectedZoneId>;
 
 #[derive(Debug)]
-pub struct GameBoardConnectedZones([[ConnectedZoneCell; GAME_BOARD_SIZE]; GAME_BOARD_SIZE]);
-
-impl GameBoardConnectedZones {
-    pub fn new(game_board: &GameBoard) -> Self {
-        let mut connected_zones =
-            GameBoardConnectedZones([[None; GAME_BOARD_SIZE]; GAME_BOARD_SIZE]);
-        let mut zone_id = ConnectedZoneId(1);
-        for location in GameBoardLocation::zero().walk(0, 1) {
-            for location in location.walk(1, 0) {
-                if connected_zones.0[location.y_offset()][location.x_offset()].is_none() {
-                    connected_zones.walk_and_mark(game_board, &location, zone_id);
-                    zone_id.0 += 1;
-                }
+pub struct SizedGameBoardConnectedZones<const N: usize>(
+    [[ConnectedZoneCell; N]; N],
+    Vec<(ConnectedZoneId, BitBoard)>,
+);
+
+impl<const N: usize> SizedGameBoardConnectedZones<N> {
+    pub fn new(game_board: &SizedGameBoard<N>) -> Self {
+        let mut connected_zones = SizedGameBoardConnectedZones([[None; N]; N], Vec::new());
+        let mut unmarked_cells = game_board.empty_cells();
+        while let Some(index) = unmarked_cells.lowest_index() {
+            // Zones are numbered in the scan order, where every ball also takes up an id.
+            let zone_id = ConnectedZoneId(
+                (1 + connected_zones.1.len() + (game_board.occupied & BitBoard::below(index)).count())
+                    as u8,
+            );
+            let zone_cells = BitBoard::from_index(index).flood::<N>(unmarked_cells);
+            unmarked_cells &= !zone_cells;
+            for zone_index in zone_cells.indices() {
+                let (x_offset, y_offset) = BitBoard::offsets_of::<N>(zone_index);
+                connected_zones.0[y_offset][x_offset] = Some(zone_id);
             }
+            connected_zones.1.push((zone_id, zone_cells));
         }
         connected_zones
     }
 
-    fn walk_and_mark(
-        &mut self,
-        game_board: &GameBoard,
-        current_location: &GameBoardLocation,
-        zone_id: ConnectedZoneId,
-    ) {
-        if let Some(current_location_zone_id) = self.at(current_location) {
-            assert_eq!(
-                current_location_zone_id,
-                zone_id,
-                "Each cell can only belong to a single connection zones."
-            );
-            return;
-        }
-        if !game_board.at(current_location).is_empty() {
-            return;
-        }
-        self.0[current_location.y_offset()][current_location.x_offset()] = Some(zone_id);
-        for neighbour_location in current_location.neighbours() {
-            self.walk_and_mark(game_board, &neighbour_location, zone_id);
+    #[inline]
+    pub fn at(&self, location: &SizedGameBoardLocation<N>) -> ConnectedZoneCell {
+        self.0[location.y_offset()][location.x_offset()]
+    }
+
+    fn reachable_cells(&self, location: &SizedGameBoardLocation<N>) -> BitBoard {
+        let neighbour_cells =
+            BitBoard::from_index(SizedGameBoard::index_of(location)).neighbours::<N>();
+        let mut reachable_cells = BitBoard::EMPTY;
+        for &(_, zone_cells) in &self.1 {
+            if !(zone_cells & neighbour_cells).is_empty() {
+                reachable_cells |= zone_cells;
+            }
         }
+        reachable_cells
     }
 
-    #[inline]
-    pub fn at(&self, location: &GameBoardLocation) -> ConnectedZoneCell {
-        self.0[location.y_offset()][location.x_offset()]
+    /// Lists the empty cells that a ball at `location` can be moved to.
+    pub fn reachable_from(
+        &self,
+        location: &SizedGameBoardLocation<N>,
+    ) -> Vec<SizedGameBoardLocation<N>> {
+        self.reachable_cells(location)
+            .indices()
+            .map(SizedGameBoard::<N>::location_of)
+            .collect()
     }
 }
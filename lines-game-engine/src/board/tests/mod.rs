@@ -1,6 +1,8 @@
 use super::super::common::{BallColor, GameScore, GAME_BOARD_SIZE};
-use super::super::location::GameBoardLocation;
-use super::{ConnectedZoneId, GameBoard};
+use super::super::location::{GameBoardLocation, GameBoardMove, SizedGameBoardLocation};
+use super::super::outcome::{ClearedLine, LineDirection};
+use super::super::rules::RuleSet;
+use super::{ConnectedZoneId, GameBoard, SizedGameBoard};
 
 #[test]
 fn game_board_is_empty_on_creation() {
@@ -18,7 +20,8 @@ fn add_ball_expect_score(
     assert_eq!(
         game_board
             .add_ball(&GameBoardLocation::from_coords(x, y).unwrap(), ball_color)
-            .unwrap(),
+            .unwrap()
+            .score,
         expected_score
     );
 }
@@ -30,7 +33,7 @@ fn add_ball_zero_score(game_board: &mut GameBoard, x: usize, y: usize, ball_colo
 #[test]
 fn game_board_can_be_completely_filled_with_balls() {
     let mut game_board = GameBoard::default();
-    let colors = vec![BallColor::Red, BallColor::Green, BallColor::Blue];
+    let colors = [BallColor::Red, BallColor::Green, BallColor::Blue];
     for y in 1..GAME_BOARD_SIZE + 1 {
         for x in 1..GAME_BOARD_SIZE + 1 {
             add_ball_zero_score(&mut game_board, x, y, colors[((x * 7 + y * 11) % 17) % 3]);
@@ -39,6 +42,34 @@ fn game_board_can_be_completely_filled_with_balls() {
     assert!(game_board.is_full());
 }
 
+#[test]
+fn sized_game_board_can_be_completely_filled_with_balls() {
+    fn fill<const N: usize>() {
+        let mut game_board = SizedGameBoard::<N>::default();
+        let colors = [BallColor::Red, BallColor::Green, BallColor::Blue];
+        for y in 1..N + 1 {
+            for x in 1..N + 1 {
+                assert_eq!(
+                    game_board
+                        .add_ball(
+                            &SizedGameBoardLocation::from_coords(x, y).unwrap(),
+                            colors[((x * 7 + y * 11) % 17) % 3]
+                        )
+                        .unwrap()
+                        .score,
+                    GameScore::new(0)
+                );
+            }
+        }
+        assert!(game_board.is_full());
+        assert!(SizedGameBoardLocation::<N>::from_coords(N + 1, 1).is_err());
+    }
+    fill::<7>();
+    fill::<10>();
+    fill::<12>();
+    fill::<15>();
+}
+
 #[test]
 fn game_board_should_drop_horizontal_lines() {
     let mut game_board = GameBoard::default();
@@ -124,6 +155,34 @@ fn game_board_should_drop_only_same_color_lines() {
     assert!(game_board.is_empty());
 }
 
+#[test]
+fn game_board_should_follow_rule_set() {
+    let rules = RuleSet::new(4, 5, 3, 5).unwrap();
+    let mut game_board = GameBoard::with_rules(rules).unwrap();
+    add_ball_zero_score(&mut game_board, 1, 1, BallColor::Cyan);
+    add_ball_zero_score(&mut game_board, 2, 1, BallColor::Cyan);
+    add_ball_zero_score(&mut game_board, 3, 1, BallColor::Cyan);
+    add_ball_expect_score(&mut game_board, 4, 1, BallColor::Cyan, GameScore::new(10));
+    assert!(game_board.is_empty());
+
+    assert!(
+        game_board
+            .add_ball(&GameBoardLocation::zero(), BallColor::Yellow)
+            .is_err()
+    );
+    assert!(SizedGameBoard::<3>::with_rules(rules).is_err());
+}
+
+#[test]
+fn game_board_parse_with_rules_should_reject_disallowed_colors() {
+    let rules = RuleSet::new(4, 5, 3, 5).unwrap();
+    let mut game_board_str = GameBoard::default().to_string();
+    assert!(GameBoard::parse_with_rules(&game_board_str, rules).is_ok());
+    game_board_str.replace_range(0..1, "Y");
+    assert!(GameBoard::parse_with_rules(&game_board_str, RuleSet::default()).is_ok());
+    assert!(GameBoard::parse_with_rules(&game_board_str, rules).is_err());
+}
+
 #[test]
 fn game_board_has_single_zone_on_empty_board() {
     let game_board_connected_zones = GameBoard::default().connected_zones();
@@ -140,7 +199,7 @@ fn game_board_has_single_zone_on_empty_board() {
 #[test]
 fn game_board_has_no_zones_when_completely_filled() {
     let mut game_board = GameBoard::default();
-    let colors = vec![BallColor::Red, BallColor::Green, BallColor::Blue];
+    let colors = [BallColor::Red, BallColor::Green, BallColor::Blue];
     for y in 1..GAME_BOARD_SIZE + 1 {
         for x in 1..GAME_BOARD_SIZE + 1 {
             add_ball_zero_score(&mut game_board, x, y, colors[((x * 7 + y * 11) % 17) % 3]);
@@ -247,3 +306,236 @@ fn game_board_from_str_should_fail_on_incomplete_string_map_format() {
     );
     assert!(game_board.is_err());
 }
+
+#[test]
+fn sized_game_board_should_drop_lines_near_far_edge() {
+    let mut game_board = SizedGameBoard::<12>::default();
+    for y in 8..12 {
+        game_board
+            .add_ball(
+                &SizedGameBoardLocation::from_coords(12, y).unwrap(),
+                BallColor::Red,
+            )
+            .unwrap();
+    }
+    assert_eq!(
+        game_board
+            .add_ball(
+                &SizedGameBoardLocation::from_coords(12, 12).unwrap(),
+                BallColor::Red,
+            )
+            .unwrap()
+            .score,
+        GameScore::new(10)
+    );
+    assert!(game_board.is_empty());
+}
+
+#[test]
+fn sized_game_board_can_be_loaded_and_displayed() {
+    use std::str::FromStr;
+    let game_board_str = "\
+                          R G B P M C Y\n\
+                          _ _ _ _ _ _ _\n\
+                          _ _ _ _ _ _ _\n\
+                          _ _ _ _ _ _ _\n\
+                          _ _ _ _ _ _ _\n\
+                          _ _ _ _ _ _ _\n\
+                          _ _ _ _ _ _ Y\n\
+                          ";
+    let game_board = SizedGameBoard::<7>::from_str(game_board_str).unwrap();
+    assert_eq!(game_board.to_string(), game_board_str);
+    assert!(GameBoard::from_str(game_board_str).is_err());
+}
+
+#[test]
+fn game_board_find_path_returns_shortest_route() {
+    let mut game_board = GameBoard::default();
+    for y in 1..5 {
+        add_ball_zero_score(&mut game_board, 2, y, BallColor::Green);
+    }
+    add_ball_zero_score(&mut game_board, 1, 1, BallColor::Red);
+    let game_move = "1 1 3 1".parse::<GameBoardMove>().unwrap();
+    let path = game_board.find_path(&game_move).unwrap();
+    assert_eq!(path.len(), 11);
+    assert_eq!(path[0], game_move.from);
+    assert_eq!(path[path.len() - 1], game_move.to);
+    for (location, next_location) in path.iter().zip(path.iter().skip(1)) {
+        assert!(location.neighbours().any(|neighbour| neighbour == *next_location));
+        assert!(game_board.at(next_location).is_empty());
+    }
+
+    let game_move = "1 1 1 2".parse::<GameBoardMove>().unwrap();
+    assert_eq!(
+        game_board.find_path(&game_move).unwrap(),
+        vec![game_move.from, game_move.to]
+    );
+}
+
+#[test]
+fn game_board_find_path_fails_when_move_is_impossible() {
+    let mut game_board = GameBoard::default();
+    add_ball_zero_score(&mut game_board, 1, 1, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 2, 1, BallColor::Green);
+    add_ball_zero_score(&mut game_board, 1, 2, BallColor::Green);
+    for game_move in &["1 1 5 5", "5 5 3 3", "1 1 2 1", "1 1 1 1"] {
+        let game_move = game_move.parse::<GameBoardMove>().unwrap();
+        assert!(game_board.find_path(&game_move).is_none());
+        assert!(game_board.move_ball(&game_move).is_err());
+    }
+}
+
+#[test]
+fn game_board_legal_moves_cover_every_ball_on_open_board() {
+    let mut game_board = GameBoard::default();
+    assert_eq!(game_board.legal_moves().count(), 0);
+    add_ball_zero_score(&mut game_board, 5, 5, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 1, 9, BallColor::Blue);
+    assert_eq!(
+        game_board.legal_moves().count(),
+        2 * (GAME_BOARD_SIZE * GAME_BOARD_SIZE - 2)
+    );
+    assert_eq!(
+        game_board
+            .reachable_from(&GameBoardLocation::from_coords(5, 5).unwrap())
+            .len(),
+        GAME_BOARD_SIZE * GAME_BOARD_SIZE - 2
+    );
+    assert!(game_board.reachable_from(&GameBoardLocation::zero()).is_empty());
+}
+
+#[test]
+fn game_board_legal_moves_match_move_ball() {
+    let mut game_board = GameBoard::default();
+    let colors = [BallColor::Red, BallColor::Green, BallColor::Blue];
+    for y in 1..GAME_BOARD_SIZE + 1 {
+        for x in 1..GAME_BOARD_SIZE + 1 {
+            if (x * 5 + y * 3) % 4 == 0 || x == 4 {
+                add_ball_zero_score(&mut game_board, x, y, colors[(x + y) % 3]);
+            }
+        }
+    }
+    let legal_moves = game_board.legal_moves().collect::<Vec<_>>();
+    assert!(!legal_moves.is_empty());
+    for from in GameBoardLocation::all() {
+        let reachable_locations = game_board.reachable_from(&from);
+        for to in GameBoardLocation::all() {
+            let game_move = GameBoardMove { from, to };
+            let is_legal = legal_moves.contains(&game_move);
+            assert_eq!(is_legal, reachable_locations.contains(&to));
+            assert_eq!(is_legal, game_board.find_path(&game_move).is_some());
+        }
+    }
+}
+
+#[test]
+fn game_board_move_outcome_describes_cleared_lines() {
+    let mut game_board = GameBoard::default();
+    add_ball_zero_score(&mut game_board, 7, 8, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 6, 8, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 5, 8, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 4, 8, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 8, 7, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 8, 6, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 8, 5, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 8, 4, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 9, 9, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 1, 1, BallColor::Blue);
+    add_ball_zero_score(&mut game_board, 1, 2, BallColor::Blue);
+    let game_move = "9 9 8 8".parse::<GameBoardMove>().unwrap();
+    let move_outcome = game_board.move_ball(&game_move).unwrap();
+    assert_eq!(
+        move_outcome.cleared_lines,
+        vec![
+            ClearedLine {
+                direction: LineDirection::Horizontal,
+                start: GameBoardLocation::from_coords(4, 8).unwrap(),
+                length: 5,
+                ball_color: BallColor::Red,
+            },
+            ClearedLine {
+                direction: LineDirection::Vertical,
+                start: GameBoardLocation::from_coords(8, 4).unwrap(),
+                length: 5,
+                ball_color: BallColor::Red,
+            },
+        ]
+    );
+    assert_eq!(move_outcome.removed_locations.len(), 9);
+    assert!(move_outcome.removed_locations.contains(&game_move.to));
+    assert_eq!(move_outcome.dropped_balls_count(), 10);
+    assert_eq!(move_outcome.score, GameScore::new(72));
+    assert!(
+        move_outcome
+            .removed_locations
+            .iter()
+            .all(|location| game_board.at(location).is_empty())
+    );
+    assert!(!game_board.is_empty());
+
+    let move_outcome = game_board
+        .move_ball(&"1 1 5 5".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    assert!(!move_outcome.is_scored());
+    assert!(move_outcome.removed_locations.is_empty());
+    assert_eq!(move_outcome.score, GameScore::new(0));
+}
+
+#[test]
+fn game_board_zobrist_hash_is_kept_in_sync() {
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    let mut game_board = GameBoard::default();
+    assert_eq!(game_board.zobrist_hash(), 0);
+    for x in 1..5 {
+        add_ball_zero_score(&mut game_board, x, 3, BallColor::Maroon);
+    }
+    add_ball_zero_score(&mut game_board, 9, 9, BallColor::Maroon);
+    add_ball_zero_score(&mut game_board, 7, 7, BallColor::Blue);
+    let reloaded_game_board = GameBoard::from_str(&game_board.to_string()).unwrap();
+    assert_eq!(game_board.zobrist_hash(), reloaded_game_board.zobrist_hash());
+    assert_eq!(game_board, reloaded_game_board);
+
+    let mut positions = HashSet::new();
+    positions.insert(game_board.clone());
+    assert!(positions.contains(&reloaded_game_board));
+
+    let move_outcome = game_board
+        .move_ball(&"9 9 5 3".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    assert!(move_outcome.is_scored());
+    let mut expected_game_board = GameBoard::default();
+    add_ball_zero_score(&mut expected_game_board, 7, 7, BallColor::Blue);
+    assert_eq!(game_board.zobrist_hash(), expected_game_board.zobrist_hash());
+    assert_eq!(game_board, expected_game_board);
+    assert!(!positions.contains(&game_board));
+
+    game_board
+        .undo_move_ball(&"9 9 5 3".parse::<GameBoardMove>().unwrap(), &move_outcome)
+        .unwrap();
+    assert_eq!(game_board, reloaded_game_board);
+    assert!(positions.contains(&game_board));
+}
+
+#[test]
+fn game_board_zobrist_hash_does_not_depend_on_move_order() {
+    let mut game_board = GameBoard::default();
+    add_ball_zero_score(&mut game_board, 1, 1, BallColor::Red);
+    add_ball_zero_score(&mut game_board, 2, 2, BallColor::Green);
+    let mut other_game_board = game_board.clone();
+    game_board
+        .move_ball(&"1 1 5 5".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    game_board
+        .move_ball(&"2 2 6 6".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    other_game_board
+        .move_ball(&"2 2 6 6".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    other_game_board
+        .move_ball(&"1 1 5 5".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    assert_eq!(game_board.zobrist_hash(), other_game_board.zobrist_hash());
+    assert_eq!(game_board, other_game_board);
+}
@@ -1,10 +1,15 @@
 use std::fmt;
+use std::ops;
 
-use failure;
+use super::error::GameError;
 
 pub const GAME_BOARD_SIZE: usize = 9;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+pub type GameIteration = u32;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum BallColor {
     Green,
     Pink,
@@ -16,6 +21,20 @@ pub enum BallColor {
 }
 
 impl BallColor {
+    pub const ALL: [BallColor; 7] = [
+        BallColor::Green,
+        BallColor::Pink,
+        BallColor::Red,
+        BallColor::Maroon,
+        BallColor::Cyan,
+        BallColor::Blue,
+        BallColor::Yellow,
+    ];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
     pub fn to_char(&self) -> char {
         use self::BallColor::*;
         match *self {
@@ -29,7 +48,7 @@ impl BallColor {
         }
     }
 
-    pub fn from_char(c: char) -> Result<Self, failure::Error> {
+    pub fn from_char(c: char) -> Result<Self, GameError> {
         use self::BallColor::*;
         Ok(match c {
             'G' => Green,
@@ -40,21 +59,40 @@ impl BallColor {
             'B' => Blue,
             'Y' => Yellow,
             _ => {
-                bail!("'{}' is not a supported color code", c);
+                return Err(GameError::UnsupportedColorCode { code: c });
             }
         })
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Constructor, Into, Add, AddAssign,
-         Display, FromStr)]
+/// The scores saturate at the largest one rather than overflowing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Constructor, Into, Display,
+         FromStr)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct GameScore(u32);
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+impl ops::Add for GameScore {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        GameScore(self.0.saturating_add(other.0))
+    }
+}
+
+impl ops::AddAssign for GameScore {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct GameBoardCell(pub Option<BallColor>);
 
 impl GameBoardCell {
-    pub fn from_char(c: char) -> Result<Self, failure::Error> {
+    pub fn from_char(c: char) -> Result<Self, GameError> {
         match c {
             '_' => Ok(GameBoardCell(None)),
             _ => Ok(GameBoardCell(Some(BallColor::from_char(c)?))),
@@ -80,3 +118,37 @@ impl fmt::Display for GameBoardCell {
         Ok(())
     }
 }
+
+/// Splits `s` into words separated by the `is_separator` characters and reports the 1-based line
+/// and column (in characters) where every word starts.
+pub(crate) fn words_with_positions<F: Fn(char) -> bool>(
+    s: &str,
+    is_separator: F,
+) -> Vec<(usize, usize, &str)> {
+    let mut words = Vec::new();
+    for (line_index, line) in s.lines().enumerate() {
+        let mut word_start = None;
+        for (column_index, (byte_index, c)) in line.char_indices().enumerate() {
+            if is_separator(c) {
+                if let Some((column, start_byte_index)) = word_start.take() {
+                    words.push((line_index + 1, column, &line[start_byte_index..byte_index]));
+                }
+            } else if word_start.is_none() {
+                word_start = Some((column_index + 1, byte_index));
+            }
+        }
+        if let Some((column, start_byte_index)) = word_start {
+            words.push((line_index + 1, column, &line[start_byte_index..]));
+        }
+    }
+    words
+}
+
+/// The 1-based line and column right after the last character of `s`.
+pub(crate) fn end_position(s: &str) -> (usize, usize) {
+    s.lines()
+        .enumerate()
+        .last()
+        .map(|(line_index, line)| (line_index + 1, line.chars().count() + 1))
+        .unwrap_or((1, 1))
+}
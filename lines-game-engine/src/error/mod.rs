@@ -0,0 +1,193 @@
+use std::error;
+use std::fmt;
+
+use super::common::BallColor;
+use super::outcome::GameOverReason;
+
+#[cfg(test)]
+mod tests;
+
+/// Locations are reported in 1-based coordinates, the same way they are written in the text
+/// protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum GameError {
+    OutOfBoard {
+        x: usize,
+        y: usize,
+        board_size: usize,
+    },
+    NoBallAtSource {
+        x: usize,
+        y: usize,
+    },
+    DestinationOccupied {
+        x: usize,
+        y: usize,
+    },
+    NoPath {
+        from: (usize, usize),
+        to: (usize, usize),
+    },
+    ColorNotAllowed {
+        ball_color: BallColor,
+        colors_count: usize,
+    },
+    UnsupportedColorCode {
+        code: char,
+    },
+    InvalidLineSize {
+        line_size: usize,
+    },
+    LineSizeExceedsBoard {
+        line_size: usize,
+        board_size: usize,
+    },
+    InvalidColorsCount {
+        colors_count: usize,
+    },
+    HistoryMismatch,
+    UnsupportedRngVersion {
+        version: u32,
+    },
+    GameIsOver {
+        reason: GameOverReason,
+    },
+    UnclearedLine {
+        x: usize,
+        y: usize,
+        length: usize,
+    },
+    ParseError {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl GameError {
+    pub(crate) fn parse_error<M: fmt::Display>(line: usize, column: usize, message: M) -> Self {
+        GameError::ParseError {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        use self::GameError::*;
+        match *self {
+            OutOfBoard { x, y, board_size } => write!(
+                fmt,
+                "The location ({}, {}) is out of the {}x{} board.",
+                x, y, board_size, board_size
+            ),
+            NoBallAtSource { x, y } => write!(fmt, "There is no ball in ({}, {}).", x, y),
+            DestinationOccupied { x, y } => write!(
+                fmt,
+                "The place ({}, {}) is already taken by another ball.",
+                x, y
+            ),
+            NoPath { from, to } => write!(
+                fmt,
+                "There is no way to move the ball from ({}, {}) to ({}, {}).",
+                from.0, from.1, to.0, to.1
+            ),
+            ColorNotAllowed {
+                ball_color,
+                colors_count,
+            } => write!(
+                fmt,
+                "{:?} ball is not allowed by the rule set (colors_count={}).",
+                ball_color, colors_count
+            ),
+            UnsupportedColorCode { code } => {
+                write!(fmt, "'{}' is not a supported color code.", code)
+            }
+            InvalidLineSize { line_size } => write!(
+                fmt,
+                "Line size ({}) should be at least 2 balls.",
+                line_size
+            ),
+            LineSizeExceedsBoard {
+                line_size,
+                board_size,
+            } => write!(
+                fmt,
+                "Line size ({}) should not exceed the board size ({}).",
+                line_size, board_size
+            ),
+            InvalidColorsCount { colors_count } => write!(
+                fmt,
+                "Colors count ({}) should be in the range from 1 to {}.",
+                colors_count,
+                BallColor::ALL.len()
+            ),
+            HistoryMismatch => write!(fmt, "The history entry does not match the game board."),
+            UnsupportedRngVersion { version } => write!(
+                fmt,
+                "Spawn RNG version {} is not supported (only version {} is).",
+                version,
+                ::random::SpawnRng::VERSION
+            ),
+            GameIsOver { reason } => write!(fmt, "The game is over ({}).", reason),
+            UnclearedLine { x, y, length } => write!(
+                fmt,
+                "The line of {} balls starting at ({}, {}) should have been cleared.",
+                length, x, y
+            ),
+            ParseError {
+                line,
+                column,
+                ref message,
+            } => write!(
+                fmt,
+                "Parse error at line {}, column {}: {}",
+                line, column, message
+            ),
+        }
+    }
+}
+
+impl error::Error for GameError {}
+
+/// A ball picked by the spawner could not be put on the board, so the spawner (or the jury files
+/// it was built from) is broken rather than the player move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnError(pub GameError);
+
+impl fmt::Display for SpawnError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "The spawned ball could not be placed: {}", self.0)
+    }
+}
+
+impl error::Error for SpawnError {}
+
+/// Why a turn could not be played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TurnError {
+    /// The player move is not allowed by the rules (or the game is over already).
+    InvalidMove(GameError),
+    Spawn(SpawnError),
+}
+
+impl From<SpawnError> for TurnError {
+    fn from(spawn_error: SpawnError) -> Self {
+        TurnError::Spawn(spawn_error)
+    }
+}
+
+impl fmt::Display for TurnError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            TurnError::InvalidMove(ref game_error) => write!(fmt, "{}", game_error),
+            TurnError::Spawn(ref spawn_error) => write!(fmt, "{}", spawn_error),
+        }
+    }
+}
+
+impl error::Error for TurnError {}
@@ -0,0 +1,122 @@
+use super::super::board::GameBoard;
+use super::super::common::BallColor;
+use super::super::location::{GameBoardLocation, GameBoardMove, SizedGameBoardLocation};
+use super::super::rules::RuleSet;
+use super::GameError;
+
+#[test]
+fn game_board_move_errors_are_typed() {
+    let mut game_board = GameBoard::default();
+    for &(x, y) in &[(1, 2), (2, 1), (2, 2)] {
+        game_board
+            .add_ball(&GameBoardLocation::from_coords(x, y).unwrap(), BallColor::Red)
+            .unwrap();
+    }
+    game_board
+        .add_ball(&GameBoardLocation::from_coords(1, 1).unwrap(), BallColor::Blue)
+        .unwrap();
+
+    assert_eq!(
+        game_board.move_ball(&"5 5 6 6".parse::<GameBoardMove>().unwrap()),
+        Err(GameError::NoBallAtSource { x: 5, y: 5 })
+    );
+    assert_eq!(
+        game_board.move_ball(&"2 2 1 2".parse::<GameBoardMove>().unwrap()),
+        Err(GameError::DestinationOccupied { x: 1, y: 2 })
+    );
+    assert_eq!(
+        game_board.move_ball(&"1 1 9 9".parse::<GameBoardMove>().unwrap()),
+        Err(GameError::NoPath {
+            from: (1, 1),
+            to: (9, 9),
+        })
+    );
+    assert_eq!(
+        game_board.add_ball(&GameBoardLocation::from_coords(1, 1).unwrap(), BallColor::Green),
+        Err(GameError::DestinationOccupied { x: 1, y: 1 })
+    );
+}
+
+#[test]
+fn game_board_location_errors_are_typed() {
+    assert_eq!(
+        GameBoardLocation::from_coords(0, 3),
+        Err(GameError::OutOfBoard {
+            x: 0,
+            y: 3,
+            board_size: 9,
+        })
+    );
+    assert_eq!(
+        SizedGameBoardLocation::<5>::from_offsets(5, 0),
+        Err(GameError::OutOfBoard {
+            x: 6,
+            y: 1,
+            board_size: 5,
+        })
+    );
+    let mut location = GameBoardLocation::zero();
+    assert_eq!(
+        location.update(-1, 2),
+        Err(GameError::OutOfBoard {
+            x: 0,
+            y: 3,
+            board_size: 9,
+        })
+    );
+    assert_eq!(location, GameBoardLocation::zero());
+    assert_eq!(
+        "1 10 2 2".parse::<GameBoardMove>(),
+        Err(GameError::OutOfBoard {
+            x: 1,
+            y: 10,
+            board_size: 9,
+        })
+    );
+}
+
+#[test]
+fn parse_errors_point_to_line_and_column() {
+    match "1 2 x 4".parse::<GameBoardMove>() {
+        Err(GameError::ParseError { line, column, .. }) => assert_eq!((line, column), (1, 5)),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    match "1 2 3\n".parse::<GameBoardMove>() {
+        Err(GameError::ParseError { line, column, .. }) => assert_eq!((line, column), (1, 6)),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    match "line_size=4, colors=3".parse::<RuleSet>() {
+        Err(GameError::ParseError { line, column, .. }) => assert_eq!((line, column), (1, 14)),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    let mut game_board_str = GameBoard::default().to_string();
+    game_board_str.replace_range(22..23, "X");
+    match game_board_str.parse::<GameBoard>() {
+        Err(GameError::ParseError { line, column, .. }) => assert_eq!((line, column), (2, 5)),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    match "_ _ _".parse::<GameBoard>() {
+        Err(GameError::ParseError { line, column, .. }) => assert_eq!((line, column), (1, 6)),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn rule_set_errors_are_typed() {
+    assert_eq!(
+        RuleSet::new(1, 7, 3, 5),
+        Err(GameError::InvalidLineSize { line_size: 1 })
+    );
+    assert_eq!(
+        RuleSet::new(5, 8, 3, 5),
+        Err(GameError::InvalidColorsCount { colors_count: 8 })
+    );
+    assert_eq!(
+        GameBoard::with_rules(RuleSet::new(10, 7, 3, 5).unwrap()).err(),
+        Some(GameError::LineSizeExceedsBoard {
+            line_size: 10,
+            board_size: 9,
+        })
+    );
+}
@@ -0,0 +1,172 @@
+use super::board::SizedGameBoard;
+use super::common::{BallColor, GAME_BOARD_SIZE};
+use super::error::GameError;
+use super::location::{SizedGameBoardLocation, SizedGameBoardMove};
+use super::outcome::SizedMoveOutcome;
+
+#[cfg(test)]
+mod tests;
+
+pub type GameHistory = SizedGameHistory<GAME_BOARD_SIZE>;
+pub type GameHistoryEntry = SizedGameHistoryEntry<GAME_BOARD_SIZE>;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SizedGameHistoryEntry<const N: usize> {
+    AddBall {
+        location: SizedGameBoardLocation<N>,
+        ball_color: BallColor,
+        move_outcome: SizedMoveOutcome<N>,
+    },
+    MoveBall {
+        game_move: SizedGameBoardMove<N>,
+        move_outcome: SizedMoveOutcome<N>,
+    },
+}
+
+impl<const N: usize> SizedGameHistoryEntry<N> {
+    pub fn move_outcome(&self) -> &SizedMoveOutcome<N> {
+        match *self {
+            SizedGameHistoryEntry::AddBall {
+                ref move_outcome, ..
+            }
+            | SizedGameHistoryEntry::MoveBall {
+                ref move_outcome, ..
+            } => move_outcome,
+        }
+    }
+
+    pub fn apply(&self, game_board: &mut SizedGameBoard<N>) -> Result<(), GameError> {
+        let move_outcome = match *self {
+            SizedGameHistoryEntry::AddBall {
+                ref location,
+                ball_color,
+                ..
+            } => game_board.add_ball(location, ball_color)?,
+            SizedGameHistoryEntry::MoveBall { ref game_move, .. } => {
+                game_board.move_ball(game_move)?
+            }
+        };
+        if move_outcome != *self.move_outcome() {
+            match *self {
+                SizedGameHistoryEntry::AddBall { ref location, .. } => {
+                    game_board.undo_add_ball(location, &move_outcome)?
+                }
+                SizedGameHistoryEntry::MoveBall { ref game_move, .. } => {
+                    game_board.undo_move_ball(game_move, &move_outcome)?
+                }
+            }
+            return Err(GameError::HistoryMismatch);
+        }
+        Ok(())
+    }
+
+    pub fn revert(&self, game_board: &mut SizedGameBoard<N>) -> Result<(), GameError> {
+        match *self {
+            SizedGameHistoryEntry::AddBall {
+                ref location,
+                ref move_outcome,
+                ..
+            } => game_board.undo_add_ball(location, move_outcome),
+            SizedGameHistoryEntry::MoveBall {
+                ref game_move,
+                ref move_outcome,
+            } => game_board.undo_move_ball(game_move, move_outcome),
+        }
+    }
+}
+
+/// Journals the changes made to a game board, so they can be undone (and redone) later.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SizedGameHistory<const N: usize> {
+    undo_entries: Vec<SizedGameHistoryEntry<N>>,
+    redo_entries: Vec<SizedGameHistoryEntry<N>>,
+}
+
+impl<const N: usize> SizedGameHistory<N> {
+    pub fn new() -> Self {
+        Self {
+            undo_entries: Vec::new(),
+            redo_entries: Vec::new(),
+        }
+    }
+
+    pub fn entries(&self) -> &[SizedGameHistoryEntry<N>] {
+        &self.undo_entries
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_entries.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_entries.is_empty()
+    }
+
+    pub fn add_ball(
+        &mut self,
+        game_board: &mut SizedGameBoard<N>,
+        location: &SizedGameBoardLocation<N>,
+        ball_color: BallColor,
+    ) -> Result<SizedMoveOutcome<N>, GameError> {
+        let move_outcome = game_board.add_ball(location, ball_color)?;
+        self.record(SizedGameHistoryEntry::AddBall {
+            location: *location,
+            ball_color,
+            move_outcome: move_outcome.clone(),
+        });
+        Ok(move_outcome)
+    }
+
+    pub fn move_ball(
+        &mut self,
+        game_board: &mut SizedGameBoard<N>,
+        game_move: &SizedGameBoardMove<N>,
+    ) -> Result<SizedMoveOutcome<N>, GameError> {
+        let move_outcome = game_board.move_ball(game_move)?;
+        self.record(SizedGameHistoryEntry::MoveBall {
+            game_move: *game_move,
+            move_outcome: move_outcome.clone(),
+        });
+        Ok(move_outcome)
+    }
+
+    fn record(&mut self, entry: SizedGameHistoryEntry<N>) {
+        self.undo_entries.push(entry);
+        self.redo_entries.clear();
+    }
+
+    pub fn undo(
+        &mut self,
+        game_board: &mut SizedGameBoard<N>,
+    ) -> Result<Option<&SizedGameHistoryEntry<N>>, GameError> {
+        let entry = match self.undo_entries.pop() {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        if let Err(why) = entry.revert(game_board) {
+            self.undo_entries.push(entry);
+            return Err(why);
+        }
+        self.redo_entries.push(entry);
+        Ok(self.redo_entries.last())
+    }
+
+    pub fn redo(
+        &mut self,
+        game_board: &mut SizedGameBoard<N>,
+    ) -> Result<Option<&SizedGameHistoryEntry<N>>, GameError> {
+        let entry = match self.redo_entries.pop() {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        if let Err(why) = entry.apply(game_board) {
+            self.redo_entries.push(entry);
+            return Err(why);
+        }
+        self.undo_entries.push(entry);
+        Ok(self.undo_entries.last())
+    }
+}
@@ -0,0 +1,143 @@
+use super::super::board::GameBoard;
+use super::super::common::{BallColor, GameScore};
+use super::super::location::{GameBoardLocation, GameBoardMove};
+use super::GameHistory;
+
+#[test]
+fn game_history_undoes_and_redoes_line_clearing_move() {
+    let mut game_board = GameBoard::default();
+    let mut game_history = GameHistory::new();
+    assert!(!game_history.can_undo());
+    for x in 1..5 {
+        game_history
+            .add_ball(
+                &mut game_board,
+                &GameBoardLocation::from_coords(x, 1).unwrap(),
+                BallColor::Red,
+            )
+            .unwrap();
+    }
+    game_history
+        .add_ball(
+            &mut game_board,
+            &GameBoardLocation::from_coords(9, 9).unwrap(),
+            BallColor::Red,
+        )
+        .unwrap();
+    let board_before_move = game_board.to_string();
+    let game_move = "9 9 5 1".parse::<GameBoardMove>().unwrap();
+    let move_outcome = game_history.move_ball(&mut game_board, &game_move).unwrap();
+    assert_eq!(move_outcome.score, GameScore::new(10));
+    assert!(game_board.is_empty());
+
+    {
+        let undone_entry = game_history.undo(&mut game_board).unwrap().unwrap();
+        assert_eq!(undone_entry.move_outcome(), &move_outcome);
+    }
+    assert_eq!(game_board.to_string(), board_before_move);
+    assert!(game_history.can_redo());
+
+    game_history.redo(&mut game_board).unwrap().unwrap();
+    assert!(game_board.is_empty());
+    assert!(!game_history.can_redo());
+
+    while game_history.can_undo() {
+        game_history.undo(&mut game_board).unwrap();
+    }
+    assert!(game_board.is_empty());
+    assert!(game_history.undo(&mut game_board).unwrap().is_none());
+    assert_eq!(game_history.entries().len(), 0);
+}
+
+#[test]
+fn game_history_undoes_spawn_that_cleared_lines() {
+    let mut game_board = GameBoard::default();
+    for y in 2..6 {
+        game_board
+            .add_ball(&GameBoardLocation::from_coords(3, y).unwrap(), BallColor::Blue)
+            .unwrap();
+    }
+    let board_before_spawn = game_board.to_string();
+    let mut game_history = GameHistory::new();
+    let location = GameBoardLocation::from_coords(3, 6).unwrap();
+    assert!(
+        game_history
+            .add_ball(&mut game_board, &location, BallColor::Blue)
+            .unwrap()
+            .is_scored()
+    );
+    game_history.undo(&mut game_board).unwrap();
+    assert_eq!(game_board.to_string(), board_before_spawn);
+}
+
+#[test]
+fn game_history_new_action_drops_redo_entries() {
+    let mut game_board = GameBoard::default();
+    let mut game_history = GameHistory::new();
+    game_history
+        .add_ball(&mut game_board, &GameBoardLocation::zero(), BallColor::Green)
+        .unwrap();
+    game_history.undo(&mut game_board).unwrap();
+    assert!(game_history.can_redo());
+    game_history
+        .add_ball(&mut game_board, &GameBoardLocation::zero(), BallColor::Pink)
+        .unwrap();
+    assert!(!game_history.can_redo());
+    assert!(game_history.redo(&mut game_board).unwrap().is_none());
+}
+
+#[test]
+fn game_board_undo_rejects_inconsistent_board() {
+    let mut game_board = GameBoard::default();
+    let location = GameBoardLocation::zero();
+    let move_outcome = game_board.add_ball(&location, BallColor::Cyan).unwrap();
+    game_board.undo_add_ball(&location, &move_outcome).unwrap();
+    assert!(game_board.undo_add_ball(&location, &move_outcome).is_err());
+    let game_move = "1 1 2 2".parse::<GameBoardMove>().unwrap();
+    assert!(game_board.undo_move_ball(&game_move, &move_outcome).is_err());
+}
+
+#[test]
+fn game_board_failed_undo_leaves_board_unchanged() {
+    let mut game_board = GameBoard::default();
+    for x in 1..5 {
+        game_board
+            .add_ball(&GameBoardLocation::from_coords(x, 1).unwrap(), BallColor::Red)
+            .unwrap();
+    }
+    game_board
+        .add_ball(&GameBoardLocation::from_coords(5, 3).unwrap(), BallColor::Red)
+        .unwrap();
+    let game_move = "5 3 5 1".parse::<GameBoardMove>().unwrap();
+    let move_outcome = game_board.move_ball(&game_move).unwrap();
+    assert!(move_outcome.is_scored());
+
+    // The source cell of the move is taken since, so the move cannot be undone.
+    game_board
+        .add_ball(&GameBoardLocation::from_coords(5, 3).unwrap(), BallColor::Green)
+        .unwrap();
+    let board_before_undo = game_board.to_string();
+    assert!(game_board.undo_move_ball(&game_move, &move_outcome).is_err());
+    assert_eq!(game_board.to_string(), board_before_undo);
+
+    // The cleared line is no longer empty.
+    let location = GameBoardLocation::from_coords(5, 3).unwrap();
+    game_board
+        .add_ball(&GameBoardLocation::from_coords(2, 1).unwrap(), BallColor::Blue)
+        .unwrap();
+    let board_before_undo = game_board.to_string();
+    assert!(game_board.undo_add_ball(&location, &move_outcome).is_err());
+    assert_eq!(game_board.to_string(), board_before_undo);
+
+    let mut game_history = GameHistory::new();
+    game_history
+        .add_ball(&mut game_board, &GameBoardLocation::from_coords(9, 9).unwrap(), BallColor::Cyan)
+        .unwrap();
+    game_board
+        .move_ball(&"9 9 8 8".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    let board_before_undo = game_board.to_string();
+    assert!(game_history.undo(&mut game_board).is_err());
+    assert_eq!(game_board.to_string(), board_before_undo);
+    assert!(game_history.can_undo());
+}
@@ -1,16 +1,47 @@
 #[macro_use]
 extern crate derive_more;
+extern crate rand;
+#[cfg(feature = "serde")]
 #[macro_use]
-extern crate failure;
+extern crate serde;
 
 #[cfg(test)]
 #[macro_use]
 extern crate quickcheck;
+#[cfg(all(test, feature = "serde"))]
+extern crate rmp_serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
+mod bitboard;
 mod board;
 mod common;
+mod error;
+mod history;
 mod location;
+mod outcome;
+mod position;
+mod random;
+mod rules;
+#[cfg(feature = "serde")]
+mod serialization;
+mod session;
+mod spawner;
+mod zobrist;
 
-pub use common::{BallColor, GameScore, GAME_BOARD_SIZE};
-pub use board::GameBoard;
-pub use location::{GameBoardLocation, GameBoardMove};
+pub use common::{BallColor, GameBoardCell, GameIteration, GameScore, GAME_BOARD_SIZE};
+pub use error::{GameError, SpawnError, TurnError};
+pub use board::{GameBoard, GameBoardConnectedZones, SizedGameBoard,
+                SizedGameBoardConnectedZones};
+pub use history::{GameHistory, GameHistoryEntry, SizedGameHistory, SizedGameHistoryEntry};
+pub use location::{GameBoardLocation, GameBoardMove, SizedGameBoardLocation, SizedGameBoardMove};
+pub use position::{GamePosition, SizedGamePosition};
+pub use random::{derive_turn_seed, salted_seed, secret_fingerprint, SpawnRng};
+pub use outcome::{ClearedLine, GameOverReason, LineDirection, MoveOutcome, SizedClearedLine,
+                  SizedMoveOutcome, GAME_OVER_PREFIX};
+pub use rules::RuleSet;
+pub use session::{GameSession, PreviewMode, SizedGameSession, SizedSpawnedBall, SizedTurnOutcome,
+                  SpawnedBall, TurnOutcome};
+pub use spawner::{AdversarialBallSpawner, BallSpawner, LegacyBallSpawner, NextBalls,
+                  ScriptedBallSpawner, SizedNextBalls, SizedScriptedBallSpawner, SpawnVersion,
+                  UniformBallSpawner, WeightedColorBallSpawner};
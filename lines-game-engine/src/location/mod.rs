@@ -1,51 +1,60 @@
-use std;
+use std::iter;
+use std::str;
 
-use failure;
-use failure::ResultExt;
-
-use super::common::GAME_BOARD_SIZE;
+use super::common::{end_position, words_with_positions, GAME_BOARD_SIZE};
+use super::error::GameError;
 
 #[cfg(test)]
 mod tests;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub struct GameBoardLocation {
+pub type GameBoardLocation = SizedGameBoardLocation<GAME_BOARD_SIZE>;
+pub type GameBoardMove = SizedGameBoardMove<GAME_BOARD_SIZE>;
+
+pub type GameBoardNeighbours<const N: usize> = iter::Chain<
+    iter::Chain<
+        iter::Chain<
+            iter::Take<iter::Skip<SizedGameBoardWalker<N>>>,
+            iter::Take<iter::Skip<SizedGameBoardWalker<N>>>,
+        >,
+        iter::Take<iter::Skip<SizedGameBoardWalker<N>>>,
+    >,
+    iter::Take<iter::Skip<SizedGameBoardWalker<N>>>,
+>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SizedGameBoardLocation<const N: usize> {
     x_offset: usize,
     y_offset: usize,
 }
 
-impl GameBoardLocation {
+impl<const N: usize> SizedGameBoardLocation<N> {
     #[inline]
-    pub fn from_offsets(x_offset: usize, y_offset: usize) -> Result<Self, failure::Error> {
-        ensure!(
-            x_offset < GAME_BOARD_SIZE,
-            "X offset ({}) should be less than the board size ({}).",
-            x_offset,
-            GAME_BOARD_SIZE
-        );
-        ensure!(
-            y_offset < GAME_BOARD_SIZE,
-            "Y offset ({}) should be less than the board size ({}).",
-            y_offset,
-            GAME_BOARD_SIZE
-        );
+    pub fn from_offsets(x_offset: usize, y_offset: usize) -> Result<Self, GameError> {
+        if x_offset >= N || y_offset >= N {
+            return Err(GameError::OutOfBoard {
+                x: x_offset.saturating_add(1),
+                y: y_offset.saturating_add(1),
+                board_size: N,
+            });
+        }
         Ok(Self { x_offset, y_offset })
     }
 
     #[inline]
-    pub fn from_coords(x: usize, y: usize) -> Result<Self, failure::Error> {
-        ensure!(
-            x > 0 && x <= GAME_BOARD_SIZE,
-            "X ({}) should be in the range from 1 to {} (the board size).",
-            x,
-            GAME_BOARD_SIZE
-        );
-        ensure!(
-            y > 0 && y <= GAME_BOARD_SIZE,
-            "Y ({}) should be in the range from 1 to {} (the board size).",
-            y,
-            GAME_BOARD_SIZE
-        );
+    pub(crate) fn from_offsets_unchecked(x_offset: usize, y_offset: usize) -> Self {
+        debug_assert!(x_offset < N && y_offset < N);
+        Self { x_offset, y_offset }
+    }
+
+    #[inline]
+    pub fn from_coords(x: usize, y: usize) -> Result<Self, GameError> {
+        if x == 0 || x > N || y == 0 || y > N {
+            return Err(GameError::OutOfBoard {
+                x,
+                y,
+                board_size: N,
+            });
+        }
         Self::from_offsets(x - 1, y - 1)
     }
 
@@ -67,94 +76,56 @@ impl GameBoardLocation {
         self.y_offset
     }
 
-    pub fn update(&mut self, dx: isize, dy: isize) -> Result<(), failure::Error> {
-        let dx_u: usize;
-        let dy_u: usize;
-        if dx > 0 {
-            dx_u = dx as usize;
-            ensure!(
-                dx_u < GAME_BOARD_SIZE,
-                "dx ({}) should be less than the board size ({})",
-                dx_u,
-                GAME_BOARD_SIZE
-            );
-            ensure!(
-                GAME_BOARD_SIZE - dx_u > self.x_offset,
-                "We refuse to move (X ({}) + dx ({}) would be out of the board ({}))",
-                self.x_offset,
-                dx_u,
-                GAME_BOARD_SIZE
-            );
-            self.x_offset += dx_u;
-        } else if dx < 0 {
-            dx_u = (-dx) as usize;
-            ensure!(
-                dx_u < GAME_BOARD_SIZE,
-                "dx (-{}) should be less than the board size ({})",
-                dx_u,
-                GAME_BOARD_SIZE
-            );
-            ensure!(
-                dx_u <= self.x_offset,
-                "We refuse to move (X ({}) - dx ({}) would be out of the board ({}))",
-                self.x_offset,
-                dx_u,
-                GAME_BOARD_SIZE
-            );
-            self.x_offset -= dx_u;
-        }
-        if dy > 0 {
-            dy_u = dy as usize;
-            ensure!(
-                dy_u < GAME_BOARD_SIZE,
-                "dy ({}) should be less than the board size ({})",
-                dy_u,
-                GAME_BOARD_SIZE
-            );
-            ensure!(
-                GAME_BOARD_SIZE - dy_u > self.y_offset,
-                "We refuse to move (Y ({}) + dy ({}) would be out of the board ({}))",
-                self.y_offset,
-                dy_u,
-                GAME_BOARD_SIZE
-            );
-            self.y_offset += dy_u;
-        } else if dy < 0 {
-            dy_u = (-dy) as usize;
-            ensure!(
-                dy_u < GAME_BOARD_SIZE,
-                "dy (-{}) should be less than the board size ({})",
-                dy_u,
-                GAME_BOARD_SIZE
-            );
-            ensure!(
-                dy_u <= self.y_offset,
-                "We refuse to move (Y ({}) - dy ({}) would be out of the board ({}))",
-                self.y_offset,
-                dy_u,
-                GAME_BOARD_SIZE
-            );
-            self.y_offset -= dy_u;
-        }
+    /// The 1-based X coordinate as it is written in the text protocol.
+    #[inline]
+    pub fn x(&self) -> usize {
+        self.x_offset + 1
+    }
+
+    /// The 1-based Y coordinate as it is written in the text protocol.
+    #[inline]
+    pub fn y(&self) -> usize {
+        self.y_offset + 1
+    }
+
+    pub fn update(&mut self, dx: isize, dy: isize) -> Result<(), GameError> {
+        *self = self.offset_by(dx, dy).ok_or_else(|| {
+            let coord = |offset: usize, delta: isize| {
+                (offset as isize).saturating_add(delta).saturating_add(1).max(0) as usize
+            };
+            GameError::OutOfBoard {
+                x: coord(self.x_offset, dx),
+                y: coord(self.y_offset, dy),
+                board_size: N,
+            }
+        })?;
         Ok(())
     }
 
-    pub fn walk(&self, dx: isize, dy: isize) -> GameBoardWalker {
-        GameBoardWalker::new(*self, dx, dy)
+    #[inline]
+    fn offset_by(&self, dx: isize, dy: isize) -> Option<Self> {
+        let x_offset = (self.x_offset as isize).checked_add(dx)?;
+        let y_offset = (self.y_offset as isize).checked_add(dy)?;
+        if x_offset < 0 || y_offset < 0 || x_offset >= N as isize || y_offset >= N as isize {
+            return None;
+        }
+        Some(Self {
+            x_offset: x_offset as usize,
+            y_offset: y_offset as usize,
+        })
     }
 
-    pub fn neighbours(
-        &self,
-    ) -> std::iter::Chain<
-        std::iter::Chain<
-            std::iter::Chain<
-                std::iter::Take<std::iter::Skip<GameBoardWalker>>,
-                std::iter::Take<std::iter::Skip<GameBoardWalker>>,
-            >,
-            std::iter::Take<std::iter::Skip<GameBoardWalker>>,
-        >,
-        std::iter::Take<std::iter::Skip<GameBoardWalker>>,
-    > {
+    pub fn all() -> impl Iterator<Item = Self> {
+        Self::zero()
+            .walk(0, 1)
+            .flat_map(|line_location| line_location.walk(1, 0))
+    }
+
+    pub fn walk(&self, dx: isize, dy: isize) -> SizedGameBoardWalker<N> {
+        SizedGameBoardWalker::new(*self, dx, dy)
+    }
+
+    pub fn neighbours(&self) -> GameBoardNeighbours<N> {
         self.walk(-1, 0)
             .skip(1)
             .take(1)
@@ -164,44 +135,63 @@ impl GameBoardLocation {
     }
 }
 
-#[derive(Debug)]
-pub struct GameBoardMove {
-    pub from: GameBoardLocation,
-    pub to: GameBoardLocation,
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SizedGameBoardMove<const N: usize> {
+    pub from: SizedGameBoardLocation<N>,
+    pub to: SizedGameBoardLocation<N>,
 }
 
-impl std::str::FromStr for GameBoardMove {
-    type Err = failure::Error;
+impl<const N: usize> str::FromStr for SizedGameBoardMove<N> {
+    type Err = GameError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let coords = s.split_whitespace()
-            .map(|coordinate| coordinate.parse::<usize>())
-            .collect::<Result<Vec<usize>, _>>()
-            .context("Move coordinates are expected to be all positive integers.")?;
-        ensure!(
-            coords.len() == 4,
-            "It is expected 4 coordinates to load Game Board Move, but {} found ({:?}).",
-            coords.len(),
-            coords
-        );
+        let words = words_with_positions(s, char::is_whitespace);
+        if words.len() != 4 {
+            let (line, column) = if words.len() > 4 {
+                (words[4].0, words[4].1)
+            } else {
+                end_position(s)
+            };
+            return Err(GameError::parse_error(
+                line,
+                column,
+                format!(
+                    "It is expected 4 coordinates to load Game Board Move, but {} found.",
+                    words.len()
+                ),
+            ));
+        }
+        let mut coords = [0; 4];
+        for (coord, &(line, column, word)) in coords.iter_mut().zip(&words) {
+            *coord = word.parse::<usize>().map_err(|_| {
+                GameError::parse_error(
+                    line,
+                    column,
+                    format!(
+                        "Move coordinates are expected to be all positive integers, but '{}' \
+                         found.",
+                        word
+                    ),
+                )
+            })?;
+        }
         Ok(Self {
-            from: GameBoardLocation::from_coords(coords[0], coords[1])
-                .context("'From' location of the Game Board Move is not valid.")?,
-            to: GameBoardLocation::from_coords(coords[2], coords[3])
-                .context("'To' location of the Game Board Move is not valid.")?,
+            from: SizedGameBoardLocation::from_coords(coords[0], coords[1])?,
+            to: SizedGameBoardLocation::from_coords(coords[2], coords[3])?,
         })
     }
 }
 
 #[derive(Debug, Copy, Clone)]
-pub struct GameBoardWalker {
-    next_location: Option<GameBoardLocation>,
+pub struct SizedGameBoardWalker<const N: usize> {
+    next_location: Option<SizedGameBoardLocation<N>>,
     dx: isize,
     dy: isize,
 }
 
-impl GameBoardWalker {
-    fn new(start_location: GameBoardLocation, dx: isize, dy: isize) -> Self {
+impl<const N: usize> SizedGameBoardWalker<N> {
+    fn new(start_location: SizedGameBoardLocation<N>, dx: isize, dy: isize) -> Self {
         Self {
             next_location: Some(start_location),
             dx,
@@ -210,18 +200,13 @@ impl GameBoardWalker {
     }
 }
 
-impl Iterator for GameBoardWalker {
-    type Item = GameBoardLocation;
+impl<const N: usize> Iterator for SizedGameBoardWalker<N> {
+    type Item = SizedGameBoardLocation<N>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let current_location = self.next_location;
-        let mut is_edge_reached = false;
-        if let Some(ref mut next_location) = self.next_location {
-            is_edge_reached = next_location.update(self.dx, self.dy).is_err();
-        }
-        if is_edge_reached {
-            self.next_location = None;
-        }
+        self.next_location =
+            current_location.and_then(|location| location.offset_by(self.dx, self.dy));
         current_location
     }
 }
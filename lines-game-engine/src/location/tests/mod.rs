@@ -1,5 +1,5 @@
 use super::super::common::GAME_BOARD_SIZE;
-use super::{GameBoardLocation, GameBoardMove};
+use super::{GameBoardLocation, GameBoardMove, SizedGameBoardLocation, SizedGameBoardMove};
 
 #[test]
 fn game_board_walker_can_walk() {
@@ -13,6 +13,12 @@ fn game_board_walker_can_walk() {
     assert!(walker.next().is_none());
 }
 
+#[test]
+fn sized_game_board_walker_stops_at_board_edge() {
+    assert_eq!(SizedGameBoardLocation::<7>::zero().walk(1, 1).count(), 7);
+    assert_eq!(SizedGameBoardLocation::<12>::zero().walk(0, 1).count(), 12);
+}
+
 quickcheck! {
     fn game_board_location_from_offsets_is_valid(x_offset: usize, y_offset: usize) -> bool {
         let location = GameBoardLocation::from_offsets(x_offset, y_offset);
@@ -41,4 +47,13 @@ quickcheck! {
             game_board_move.is_err()
         }
     }
+
+    fn sized_game_board_move_from_str_is_valid(x1: usize, y1: usize, x2: usize, y2: usize) -> bool {
+        let game_board_move = format!("{} {} {} {}", x1, y1, x2, y2).parse::<SizedGameBoardMove<12>>();
+        if (x1 > 0 && x1 <= 12 && y1 > 0 && y1 <= 12) && (x2 > 0 && x2 <= 12 && y2 > 0 && y2 <= 12) {
+            game_board_move.is_ok()
+        } else {
+            game_board_move.is_err()
+        }
+    }
 }
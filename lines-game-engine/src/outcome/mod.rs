@@ -0,0 +1,150 @@
+use std::fmt;
+use std::str;
+
+use super::common::{BallColor, GameScore, GAME_BOARD_SIZE};
+use super::error::GameError;
+use super::location::SizedGameBoardLocation;
+
+pub type ClearedLine = SizedClearedLine<GAME_BOARD_SIZE>;
+pub type MoveOutcome = SizedMoveOutcome<GAME_BOARD_SIZE>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LineDirection {
+    Horizontal,
+    Vertical,
+    MainDiagonal,
+    MinorDiagonal,
+}
+
+impl LineDirection {
+    pub const ALL: [LineDirection; 4] = [
+        LineDirection::Horizontal,
+        LineDirection::Vertical,
+        LineDirection::MainDiagonal,
+        LineDirection::MinorDiagonal,
+    ];
+
+    /// The (dx, dy) step that walks along the line from its start location.
+    pub fn delta(&self) -> (isize, isize) {
+        use self::LineDirection::*;
+        match *self {
+            Horizontal => (1, 0),
+            Vertical => (0, 1),
+            MainDiagonal => (1, 1),
+            MinorDiagonal => (1, -1),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SizedClearedLine<const N: usize> {
+    pub direction: LineDirection,
+    pub start: SizedGameBoardLocation<N>,
+    pub length: usize,
+    pub ball_color: BallColor,
+}
+
+impl<const N: usize> SizedClearedLine<N> {
+    pub fn locations(&self) -> impl Iterator<Item = SizedGameBoardLocation<N>> {
+        let (dx, dy) = self.direction.delta();
+        self.start.walk(dx, dy).take(self.length)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SizedMoveOutcome<const N: usize> {
+    pub cleared_lines: Vec<SizedClearedLine<N>>,
+    pub removed_locations: Vec<SizedGameBoardLocation<N>>,
+    pub score: GameScore,
+}
+
+impl<const N: usize> SizedMoveOutcome<N> {
+    /// The number of balls the score is computed from; a ball shared by intersecting lines is
+    /// counted once per line.
+    pub fn dropped_balls_count(&self) -> usize {
+        self.cleared_lines
+            .iter()
+            .map(|cleared_line| cleared_line.length)
+            .sum()
+    }
+
+    pub fn is_scored(&self) -> bool {
+        !self.cleared_lines.is_empty()
+    }
+}
+
+impl<const N: usize> Default for SizedMoveOutcome<N> {
+    fn default() -> Self {
+        Self {
+            cleared_lines: Vec::new(),
+            removed_locations: Vec::new(),
+            score: GameScore::new(0),
+        }
+    }
+}
+
+/// Why a game has ended; written as `invalid_move`, `no_space` or `move_limit` in the text
+/// protocol.
+/// The prefix of the `GAME OVER <reason>` line the generator shows to the solution.
+pub const GAME_OVER_PREFIX: &str = "GAME OVER ";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GameOverReason {
+    /// The player made a move that is not allowed by the rules (or could not be parsed).
+    InvalidMove,
+    /// The board is full, so there is no move to make.
+    NoSpace,
+    /// The game has used up all of its moves.
+    MoveLimit,
+}
+
+impl GameOverReason {
+    pub const ALL: [GameOverReason; 3] = [
+        GameOverReason::InvalidMove,
+        GameOverReason::NoSpace,
+        GameOverReason::MoveLimit,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        use self::GameOverReason::*;
+        match *self {
+            InvalidMove => "invalid_move",
+            NoSpace => "no_space",
+            MoveLimit => "move_limit",
+        }
+    }
+}
+
+impl fmt::Display for GameOverReason {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.as_str())
+    }
+}
+
+impl str::FromStr for GameOverReason {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let reason = s.trim();
+        Self::ALL
+            .iter()
+            .find(|game_over_reason| game_over_reason.as_str() == reason)
+            .cloned()
+            .ok_or_else(|| {
+                GameError::parse_error(
+                    1,
+                    1,
+                    format!(
+                        "'{}' is not a game over reason (invalid_move, no_space or move_limit).",
+                        s
+                    ),
+                )
+            })
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::str;
+
+use super::board::SizedGameBoard;
+use super::common::{GameScore, GAME_BOARD_SIZE};
+use super::error::GameError;
+use super::location::SizedGameBoardLocation;
+use super::outcome::LineDirection;
+use super::rules::RuleSet;
+
+#[cfg(test)]
+mod tests;
+
+pub type GamePosition = SizedGamePosition<GAME_BOARD_SIZE>;
+
+/// A game board with the score, written the same way as the solution input: the board lines, an
+/// empty line and the score.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SizedGamePosition<const N: usize> {
+    pub game_board: SizedGameBoard<N>,
+    pub game_score: GameScore,
+}
+
+impl<const N: usize> SizedGamePosition<N> {
+    /// Checks that the position can happen in a game by the board rule set, i.e. that it has no
+    /// lines that should have been cleared.
+    pub fn new(game_board: SizedGameBoard<N>, game_score: GameScore) -> Result<Self, GameError> {
+        let line_size = game_board.rules().line_size();
+        for location in SizedGameBoardLocation::<N>::all() {
+            let ball_color = match game_board.at(&location).0 {
+                Some(ball_color) => ball_color,
+                None => continue,
+            };
+            for direction in &LineDirection::ALL {
+                let (dx, dy) = direction.delta();
+                let is_line_start = location
+                    .walk(-dx, -dy)
+                    .nth(1)
+                    .is_none_or(|previous| game_board.at(&previous).0 != Some(ball_color));
+                if !is_line_start {
+                    continue;
+                }
+                let length = location
+                    .walk(dx, dy)
+                    .take_while(|location| game_board.at(location).0 == Some(ball_color))
+                    .count();
+                if length >= line_size {
+                    return Err(GameError::UnclearedLine {
+                        x: location.x(),
+                        y: location.y(),
+                        length,
+                    });
+                }
+            }
+        }
+        Ok(Self {
+            game_board,
+            game_score,
+        })
+    }
+
+    pub fn parse_with_rules(s: &str, rules: RuleSet) -> Result<Self, GameError> {
+        let lines = s.lines().collect::<Vec<&str>>();
+        if lines.len() < N + 2 {
+            return Err(GameError::parse_error(
+                lines.len() + 1,
+                1,
+                format!(
+                    "A position is expected to have {} game board lines, an empty line and the \
+                     score, but it has only {} lines.",
+                    N,
+                    lines.len()
+                ),
+            ));
+        }
+        let game_board = SizedGameBoard::parse_with_rules(&lines[..N].join("\n"), rules)?;
+        if !lines[N].trim().is_empty() {
+            return Err(GameError::parse_error(
+                N + 1,
+                1,
+                "An empty line is expected between the game board and the score.",
+            ));
+        }
+        let game_score = lines[N + 1].trim().parse::<GameScore>().map_err(|_| {
+            GameError::parse_error(
+                N + 2,
+                1,
+                format!(
+                    "The score is expected to be a non-negative integer, but '{}' found.",
+                    lines[N + 1]
+                ),
+            )
+        })?;
+        if let Some(extra_line_index) = lines[N + 2..]
+            .iter()
+            .position(|line| !line.trim().is_empty())
+        {
+            return Err(GameError::parse_error(
+                N + 3 + extra_line_index,
+                1,
+                "Nothing is expected after the score.",
+            ));
+        }
+        Self::new(game_board, game_score)
+    }
+}
+
+impl<const N: usize> fmt::Display for SizedGamePosition<N> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}\n{}\n", self.game_board, self.game_score)
+    }
+}
+
+impl<const N: usize> str::FromStr for SizedGamePosition<N> {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_rules(s, RuleSet::default())
+    }
+}
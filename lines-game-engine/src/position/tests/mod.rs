@@ -0,0 +1,76 @@
+use super::super::common::{BallColor, GameScore};
+use super::super::error::GameError;
+use super::super::location::GameBoardLocation;
+use super::super::rules::RuleSet;
+use super::GamePosition;
+
+const POSITION: &str = "\
+R R R R _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ G _ _ _ _
+_ _ _ _ G _ _ _ _
+_ _ _ _ G _ _ _ _
+_ _ _ _ G _ _ _ _
+_ _ _ _ _ _ _ _ B
+
+120
+";
+
+#[test]
+fn game_position_can_be_parsed_and_formatted() {
+    let position = POSITION.parse::<GamePosition>().unwrap();
+    assert_eq!(position.game_score, GameScore::new(120));
+    assert_eq!(position.game_board.balls_count(), 9);
+    assert_eq!(
+        position
+            .game_board
+            .at(&GameBoardLocation::from_coords(9, 9).unwrap())
+            .0,
+        Some(BallColor::Blue)
+    );
+    assert_eq!(position.to_string(), POSITION);
+}
+
+#[test]
+fn game_position_should_not_have_uncleared_lines() {
+    let position = POSITION.replacen("_ _ _ _ _ _ _ _ B", "_ _ _ _ G _ _ _ B", 1);
+    assert_eq!(
+        position.parse::<GamePosition>(),
+        Err(GameError::UnclearedLine {
+            x: 5,
+            y: 5,
+            length: 5,
+        })
+    );
+    assert_eq!(
+        GamePosition::parse_with_rules(POSITION, RuleSet::new(4, 7, 3, 5).unwrap()),
+        Err(GameError::UnclearedLine {
+            x: 1,
+            y: 1,
+            length: 4,
+        })
+    );
+}
+
+#[test]
+fn game_position_from_str_should_fail_on_invalid_input() {
+    let rules = RuleSet::new(5, 5, 3, 5).unwrap();
+    assert_eq!(
+        GamePosition::parse_with_rules(POSITION, rules),
+        Err(GameError::ColorNotAllowed {
+            ball_color: BallColor::Blue,
+            colors_count: 5,
+        })
+    );
+
+    let parse_error_line = |position: &str| match position.parse::<GamePosition>() {
+        Err(GameError::ParseError { line, .. }) => line,
+        other => panic!("A parse error was expected, but {:?} found", other),
+    };
+    assert_eq!(parse_error_line(&POSITION.replace("\n120\n", "")), 10);
+    assert_eq!(parse_error_line(&POSITION.replace("\n\n120", "\n0\n120")), 10);
+    assert_eq!(parse_error_line(&POSITION.replace("120", "-1")), 11);
+    assert_eq!(parse_error_line(&(POSITION.to_string() + "\n1 1 2 2\n")), 13);
+}
@@ -0,0 +1,137 @@
+//! The random number generator used for spawning balls. It is implemented here (rather than
+//! taken from a crate) so that the spawn sequence of a game is fully defined by its seed and
+//! the generator version, and can be replayed on any platform with any future build.
+//!
+//! Version 1:
+//!
+//! * the seed of a turn is the `game_iteration + 1`-th output of SplitMix64 seeded with the game
+//!   seed;
+//! * the turn RNG is xoshiro256** with its state filled by SplitMix64 seeded with the turn seed;
+//! * a number below `bound` is the high half of the 128-bit product of the next output and
+//!   `bound`, with Lemire's rejection of the biased low halves.
+
+use super::common::GameIteration;
+use super::error::GameError;
+
+#[cfg(test)]
+mod tests;
+
+const SPLITMIX64_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+const SECRET_FINGERPRINT_SEED: u64 = 0x5ec2_e7f1_9e29_1e75;
+
+#[inline]
+pub(crate) const fn splitmix64_mix(state: u64) -> u64 {
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[inline]
+pub(crate) const fn splitmix64_next(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(SPLITMIX64_GAMMA);
+    splitmix64_mix(*state)
+}
+
+pub fn derive_turn_seed(game_seed: u64, game_iteration: GameIteration) -> u64 {
+    splitmix64_mix(
+        game_seed.wrapping_add((u64::from(game_iteration) + 1).wrapping_mul(SPLITMIX64_GAMMA)),
+    )
+}
+
+/// Mixes a secret (e.g. the contents of a file that only the jury has) into the game seed, so
+/// the spawns cannot be predicted from the public parameters of the game.
+pub fn salted_seed(game_seed: u64, secret: &[u8]) -> u64 {
+    let mut state = game_seed ^ splitmix64_mix(secret.len() as u64);
+    for chunk in secret.chunks(8) {
+        let mut word = [0; 8];
+        word[..chunk.len()].copy_from_slice(chunk);
+        state = splitmix64_mix(state ^ u64::from_le_bytes(word)).wrapping_add(SPLITMIX64_GAMMA);
+    }
+    splitmix64_next(&mut state)
+}
+
+/// A short fingerprint of the secret, to be recorded with a game, so the game is not continued
+/// with another secret (or without one). It is derived apart from the game seeds and keeps only 32
+/// bits, so it tells little about the salted seed.
+pub fn secret_fingerprint(secret: &[u8]) -> u32 {
+    (salted_seed(SECRET_FINGERPRINT_SEED, secret) >> 32) as u32
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnRng {
+    state: [u64; 4],
+}
+
+impl SpawnRng {
+    pub const VERSION: u32 = 1;
+    pub const ALGORITHM: &'static str = "xoshiro256** seeded by SplitMix64";
+
+    /// The version and the algorithm, as they are recorded in the game logs.
+    pub fn description() -> String {
+        format!("SpawnRng v{} ({})", Self::VERSION, Self::ALGORITHM)
+    }
+
+    pub fn from_seed(seed: u64) -> Self {
+        let mut splitmix64_state = seed;
+        let mut state = [0; 4];
+        for word in &mut state {
+            *word = splitmix64_next(&mut splitmix64_state);
+        }
+        Self { state }
+    }
+
+    /// Refuses the RNG version recorded with a game if it is not this one, so a game produced by
+    /// a different algorithm is not silently continued with a different spawn sequence.
+    pub fn check_version(version: u32) -> Result<(), GameError> {
+        if version != Self::VERSION {
+            return Err(GameError::UnsupportedRngVersion { version });
+        }
+        Ok(())
+    }
+
+    pub fn for_turn(game_seed: u64, game_iteration: GameIteration) -> Self {
+        Self::from_seed(derive_turn_seed(game_seed, game_iteration))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1]
+            .wrapping_mul(5)
+            .rotate_left(7)
+            .wrapping_mul(9);
+        let t = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    /// A uniformly distributed number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        self.below_u64(bound as u64) as usize
+    }
+
+    /// A uniformly distributed number in `0..bound`, for the bounds that may not fit `usize`.
+    pub fn below_u64(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "The bound should be positive.");
+        let mut product = u128::from(self.next_u64()) * u128::from(bound);
+        if (product as u64) < bound {
+            let threshold = bound.wrapping_neg() % bound;
+            while (product as u64) < threshold {
+                product = u128::from(self.next_u64()) * u128::from(bound);
+            }
+        }
+        (product >> 64) as u64
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.below(items.len())])
+        }
+    }
+}
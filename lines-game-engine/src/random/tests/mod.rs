@@ -0,0 +1,63 @@
+use super::super::error::GameError;
+use super::{derive_turn_seed, salted_seed, secret_fingerprint, splitmix64_next, SpawnRng};
+
+#[test]
+fn splitmix64_matches_the_reference_implementation() {
+    let mut state = 0;
+    assert_eq!(splitmix64_next(&mut state), 0xe220_a839_7b1d_cdaf);
+    assert_eq!(splitmix64_next(&mut state), 0x6e78_9e6a_a1b9_65f4);
+    assert_eq!(splitmix64_next(&mut state), 0x06c4_5d18_8009_454f);
+    assert_eq!(splitmix64_next(&mut state), 0xf88b_b8a8_724c_81ec);
+}
+
+#[test]
+fn spawn_rng_v1_golden_values() {
+    let mut rng = SpawnRng::from_seed(0);
+    assert_eq!(rng.next_u64(), 0x99ec_5f36_cb75_f2b4);
+    assert_eq!(rng.next_u64(), 0xbf6e_1f78_4956_452a);
+    assert_eq!(rng.next_u64(), 0x1a5f_849d_4933_e6e0);
+
+    assert_eq!(derive_turn_seed(0, 0), 0xe220_a839_7b1d_cdaf);
+    assert_eq!(derive_turn_seed(42, 7), 0xccf6_35ee_9e9e_2fa4);
+
+    let mut rng = SpawnRng::for_turn(42, 7);
+    let cells = (0..8).map(|_| rng.below(81)).collect::<Vec<_>>();
+    assert_eq!(cells, vec![66, 16, 28, 62, 9, 0, 78, 23]);
+
+    let mut rng = SpawnRng::from_seed(12345);
+    let colors = (0..8).map(|_| rng.below(7)).collect::<Vec<_>>();
+    assert_eq!(colors, vec![5, 0, 6, 0, 3, 0, 1, 2]);
+}
+
+#[test]
+fn spawn_rng_refuses_unknown_versions() {
+    assert_eq!(SpawnRng::check_version(1), Ok(()));
+    assert_eq!(
+        SpawnRng::check_version(2),
+        Err(GameError::UnsupportedRngVersion { version: 2 })
+    );
+}
+
+#[test]
+fn salted_seeds_are_stable_and_depend_on_the_secret() {
+    assert_eq!(salted_seed(0, b"jury secret"), salted_seed(0, b"jury secret"));
+    assert_ne!(salted_seed(0, b"jury secret"), salted_seed(0, b"jury secret2"));
+    assert_ne!(salted_seed(0, b"jury secret"), salted_seed(1, b"jury secret"));
+    assert_ne!(salted_seed(0, b""), salted_seed(0, b"\0"));
+    assert_ne!(salted_seed(0, b"\0"), salted_seed(0, b"\0\0"));
+}
+
+#[test]
+fn secret_fingerprints_are_stable_and_depend_on_the_secret() {
+    assert_eq!(secret_fingerprint(b"jury secret"), secret_fingerprint(b"jury secret"));
+    assert_ne!(secret_fingerprint(b"jury secret"), secret_fingerprint(b"jury secret2"));
+    assert_ne!(secret_fingerprint(b""), secret_fingerprint(b"\0"));
+}
+
+quickcheck! {
+    fn spawn_rng_below_stays_in_bounds(seed: u64, bound: usize) -> bool {
+        let bound = bound % 1000 + 1;
+        let mut rng = SpawnRng::from_seed(seed);
+        (0..100).all(|_| rng.below(bound) < bound)
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+use std::str;
+
+use super::common::{words_with_positions, BallColor, GameScore};
+use super::error::GameError;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RuleSet {
+    line_size: usize,
+    colors_count: usize,
+    balls_per_turn: usize,
+    starting_balls: usize,
+}
+
+impl RuleSet {
+    pub fn new(
+        line_size: usize,
+        colors_count: usize,
+        balls_per_turn: usize,
+        starting_balls: usize,
+    ) -> Result<Self, GameError> {
+        if line_size < 2 {
+            return Err(GameError::InvalidLineSize { line_size });
+        }
+        if colors_count == 0 || colors_count > BallColor::ALL.len() {
+            return Err(GameError::InvalidColorsCount { colors_count });
+        }
+        Ok(Self {
+            line_size,
+            colors_count,
+            balls_per_turn,
+            starting_balls,
+        })
+    }
+
+    #[inline]
+    pub fn line_size(&self) -> usize {
+        self.line_size
+    }
+
+    #[inline]
+    pub fn colors_count(&self) -> usize {
+        self.colors_count
+    }
+
+    #[inline]
+    pub fn balls_per_turn(&self) -> usize {
+        self.balls_per_turn
+    }
+
+    #[inline]
+    pub fn starting_balls(&self) -> usize {
+        self.starting_balls
+    }
+
+    pub fn ball_colors(&self) -> &'static [BallColor] {
+        &BallColor::ALL[..self.colors_count]
+    }
+
+    pub fn is_color_allowed(&self, ball_color: BallColor) -> bool {
+        ball_color.index() < self.colors_count
+    }
+
+    /// The score for dropping `number_of_dropped_balls` balls at once (possibly from several
+    /// intersecting lines). It saturates at the largest score rather than overflowing, which
+    /// short lines on a crowded board can reach.
+    pub fn score(&self, number_of_dropped_balls: usize) -> GameScore {
+        GameScore::new(if number_of_dropped_balls < self.line_size {
+            0
+        } else {
+            let exponent = (number_of_dropped_balls - self.line_size + 1).min(u32::MAX as usize);
+            1u32.checked_shl(exponent as u32)
+                .map_or(u32::MAX, |bonus| bonus.saturating_add(8))
+        })
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self {
+            line_size: 5,
+            colors_count: BallColor::ALL.len(),
+            balls_per_turn: 3,
+            starting_balls: 5,
+        }
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "line_size={} colors_count={} balls_per_turn={} starting_balls={}",
+            self.line_size, self.colors_count, self.balls_per_turn, self.starting_balls
+        )
+    }
+}
+
+/// Parses a whitespace (or comma) separated list of `key=value` pairs; the omitted keys keep
+/// their default values, so an empty string produces the default rule set.
+impl str::FromStr for RuleSet {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let default = Self::default();
+        let mut line_size = default.line_size;
+        let mut colors_count = default.colors_count;
+        let mut balls_per_turn = default.balls_per_turn;
+        let mut starting_balls = default.starting_balls;
+        for (line, column, rule) in words_with_positions(s, |c| c == ',' || c.is_whitespace()) {
+            let mut rule_parts = rule.splitn(2, '=');
+            let key = rule_parts.next().unwrap_or_default();
+            let value = rule_parts
+                .next()
+                .ok_or_else(|| {
+                    GameError::parse_error(
+                        line,
+                        column,
+                        format!("Rule '{}' is expected to be in key=value form.", rule),
+                    )
+                })?
+                .parse::<usize>()
+                .map_err(|_| {
+                    GameError::parse_error(
+                        line,
+                        column + key.chars().count() + 1,
+                        format!("Rule '{}' value is expected to be an integer.", key),
+                    )
+                })?;
+            match key {
+                "line_size" => line_size = value,
+                "colors_count" => colors_count = value,
+                "balls_per_turn" => balls_per_turn = value,
+                "starting_balls" => starting_balls = value,
+                _ => {
+                    return Err(GameError::parse_error(
+                        line,
+                        column,
+                        format!("'{}' is not a supported rule.", key),
+                    ))
+                }
+            }
+        }
+        Self::new(line_size, colors_count, balls_per_turn, starting_balls)
+    }
+}
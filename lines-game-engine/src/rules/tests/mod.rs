@@ -0,0 +1,51 @@
+use super::super::common::{BallColor, GameScore};
+use super::RuleSet;
+
+#[test]
+fn rule_set_defaults_to_classic_lines() {
+    let rules = RuleSet::default();
+    assert_eq!(rules.line_size(), 5);
+    assert_eq!(rules.ball_colors(), &BallColor::ALL[..]);
+    assert_eq!(rules.balls_per_turn(), 3);
+    assert_eq!(rules.starting_balls(), 5);
+    assert_eq!(rules.score(4), GameScore::new(0));
+    assert_eq!(rules.score(5), GameScore::new(10));
+    assert_eq!(rules.score(9), GameScore::new(40));
+}
+
+#[test]
+fn rule_set_score_saturates_instead_of_overflowing() {
+    // Dropping a ball with 8 more in each of the 4 directions takes the bonus to 1 << 32.
+    let rules = RuleSet::new(2, 7, 3, 5).unwrap();
+    assert_eq!(rules.score(32), GameScore::new((1 << 31) + 8));
+    assert_eq!(rules.score(33), GameScore::new(u32::MAX));
+    assert_eq!(rules.score(usize::MAX), GameScore::new(u32::MAX));
+    let mut game_score = GameScore::new(10);
+    game_score += rules.score(33);
+    assert_eq!(game_score, GameScore::new(u32::MAX));
+}
+
+#[test]
+fn rule_set_can_be_parsed_from_string() {
+    let rules = "line_size=4, colors_count=5".parse::<RuleSet>().unwrap();
+    assert_eq!(rules, RuleSet::new(4, 5, 3, 5).unwrap());
+    assert_eq!(rules.ball_colors().len(), 5);
+    assert!(!rules.is_color_allowed(BallColor::Yellow));
+    assert_eq!(rules.score(4), GameScore::new(10));
+
+    assert_eq!("".parse::<RuleSet>().unwrap(), RuleSet::default());
+    assert_eq!(
+        RuleSet::default().to_string().parse::<RuleSet>().unwrap(),
+        RuleSet::default()
+    );
+}
+
+#[test]
+fn rule_set_from_str_should_fail_on_invalid_rules() {
+    assert!("line_size".parse::<RuleSet>().is_err());
+    assert!("line_size=five".parse::<RuleSet>().is_err());
+    assert!("line_length=5".parse::<RuleSet>().is_err());
+    assert!("line_size=1".parse::<RuleSet>().is_err());
+    assert!("colors_count=0".parse::<RuleSet>().is_err());
+    assert!("colors_count=8".parse::<RuleSet>().is_err());
+}
@@ -0,0 +1,134 @@
+//! Serde support for the types that cannot simply derive it: locations are written as 1-based
+//! coordinates (the same as in the text protocol), rule sets, boards and positions are validated
+//! on the way in, and boards are stored as rows of cells together with the rule set they are
+//! played by.
+
+use serde::de::Error as DeserializeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::board::SizedGameBoard;
+use super::common::{GameBoardCell, GameScore};
+use super::location::SizedGameBoardLocation;
+use super::position::SizedGamePosition;
+use super::rules::RuleSet;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Serialize, Deserialize)]
+struct LocationRepr {
+    x: usize,
+    y: usize,
+}
+
+impl<const N: usize> Serialize for SizedGameBoardLocation<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        LocationRepr {
+            x: self.x_offset() + 1,
+            y: self.y_offset() + 1,
+        }.serialize(serializer)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for SizedGameBoardLocation<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let location = LocationRepr::deserialize(deserializer)?;
+        Self::from_coords(location.x, location.y).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RuleSetRepr {
+    line_size: usize,
+    colors_count: usize,
+    balls_per_turn: usize,
+    starting_balls: usize,
+}
+
+impl Serialize for RuleSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RuleSetRepr {
+            line_size: self.line_size(),
+            colors_count: self.colors_count(),
+            balls_per_turn: self.balls_per_turn(),
+            starting_balls: self.starting_balls(),
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RuleSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rules = RuleSetRepr::deserialize(deserializer)?;
+        Self::new(
+            rules.line_size,
+            rules.colors_count,
+            rules.balls_per_turn,
+            rules.starting_balls,
+        ).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct GameBoardRepr {
+    rules: RuleSet,
+    rows: Vec<Vec<GameBoardCell>>,
+}
+
+impl<const N: usize> Serialize for SizedGameBoard<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        GameBoardRepr {
+            rules: *self.rules(),
+            rows: SizedGameBoardLocation::<N>::zero()
+                .walk(0, 1)
+                .map(|line_location| {
+                    line_location
+                        .walk(1, 0)
+                        .map(|location| *self.at(&location))
+                        .collect()
+                })
+                .collect(),
+        }.serialize(serializer)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for SizedGameBoard<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let game_board = GameBoardRepr::deserialize(deserializer)?;
+        if game_board.rows.len() != N {
+            return Err(D::Error::invalid_length(
+                game_board.rows.len(),
+                &format!("{} rows", N).as_str(),
+            ));
+        }
+        let mut board_lines = Vec::with_capacity(N);
+        for row in &game_board.rows {
+            if row.len() != N {
+                return Err(D::Error::invalid_length(
+                    row.len(),
+                    &format!("{} cells in a row", N).as_str(),
+                ));
+            }
+            board_lines.push(
+                row.iter()
+                    .map(|cell| cell.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            );
+        }
+        Self::parse_with_rules(&board_lines.join("\n"), game_board.rules)
+            .map_err(D::Error::custom)
+    }
+}
+
+#[derive(Deserialize)]
+struct GamePositionRepr<const N: usize> {
+    game_board: SizedGameBoard<N>,
+    game_score: GameScore,
+}
+
+impl<'de, const N: usize> Deserialize<'de> for SizedGamePosition<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let position = GamePositionRepr::<N>::deserialize(deserializer)?;
+        Self::new(position.game_board, position.game_score).map_err(D::Error::custom)
+    }
+}
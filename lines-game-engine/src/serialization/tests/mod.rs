@@ -0,0 +1,117 @@
+use rmp_serde;
+use serde_json;
+
+use super::super::board::GameBoard;
+use super::super::common::{BallColor, GameScore};
+use super::super::error::GameError;
+use super::super::location::{GameBoardLocation, GameBoardMove};
+use super::super::position::GamePosition;
+use super::super::rules::RuleSet;
+
+#[test]
+fn simple_types_have_stable_json_schema() {
+    assert_eq!(serde_json::to_string(&BallColor::Maroon).unwrap(), r#""maroon""#);
+    assert_eq!(serde_json::to_string(&GameScore::new(42)).unwrap(), "42");
+    assert_eq!(
+        serde_json::to_string(&GameBoardLocation::from_coords(2, 7).unwrap()).unwrap(),
+        r#"{"x":2,"y":7}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&"1 2 3 4".parse::<GameBoardMove>().unwrap()).unwrap(),
+        r#"{"from":{"x":1,"y":2},"to":{"x":3,"y":4}}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&RuleSet::default()).unwrap(),
+        r#"{"line_size":5,"colors_count":7,"balls_per_turn":3,"starting_balls":5}"#
+    );    assert_eq!(
+        serde_json::to_string(&GameError::NoPath {
+            from: (1, 1),
+            to: (9, 9),
+        }).unwrap(),
+        r#"{"kind":"no_path","from":[1,1],"to":[9,9]}"#
+    );
+}
+
+#[test]
+fn game_board_has_stable_json_schema() {
+    let rules = RuleSet::new(3, 2, 1, 1).unwrap();
+    let game_board = GameBoard::parse_with_rules(
+        "G _ _ _ _ _ _ _ _\n\
+         _ _ _ _ _ _ _ _ _\n\
+         _ _ _ _ _ _ _ _ _\n\
+         _ _ _ _ _ _ _ _ _\n\
+         _ _ _ _ _ _ _ _ _\n\
+         _ _ _ _ _ _ _ _ _\n\
+         _ _ _ _ _ _ _ _ _\n\
+         _ _ _ _ _ _ _ _ _\n\
+         _ _ _ _ _ _ _ _ P",
+        rules,
+    ).unwrap();
+    let json = serde_json::to_value(&game_board).unwrap();
+    assert_eq!(json["rules"], serde_json::to_value(rules).unwrap());
+    assert_eq!(json["rows"].as_array().unwrap().len(), 9);
+    assert_eq!(json["rows"][0][0], "green");
+    assert_eq!(json["rows"][0][1], serde_json::Value::Null);
+    assert_eq!(json["rows"][8][8], "pink");
+
+    let deserialized_game_board: GameBoard = serde_json::from_value(json).unwrap();
+    assert_eq!(deserialized_game_board, game_board);
+    assert_eq!(deserialized_game_board.rules(), &rules);
+}
+
+#[test]
+fn engine_types_roundtrip_through_messagepack() {
+    let mut game_board = GameBoard::default();
+    game_board
+        .add_ball(&GameBoardLocation::from_coords(4, 5).unwrap(), BallColor::Cyan)
+        .unwrap();
+    let game_move = "4 5 9 9".parse::<GameBoardMove>().unwrap();
+
+    let bytes = rmp_serde::to_vec(&game_board).unwrap();
+    assert_eq!(rmp_serde::from_slice::<GameBoard>(&bytes).unwrap(), game_board);
+    let bytes = rmp_serde::to_vec(&game_move).unwrap();
+    assert_eq!(rmp_serde::from_slice::<GameBoardMove>(&bytes).unwrap(), game_move);
+    let outcome = game_board.move_ball(&game_move).unwrap();
+    let bytes = rmp_serde::to_vec(&outcome).unwrap();
+    assert_eq!(rmp_serde::from_slice::<::MoveOutcome>(&bytes).unwrap(), outcome);
+}
+
+#[test]
+fn deserialization_should_fail_on_invalid_values() {
+    assert!(serde_json::from_str::<BallColor>(r#""orange""#).is_err());
+    assert!(serde_json::from_str::<GameBoardLocation>(r#"{"x":0,"y":1}"#).is_err());
+    assert!(serde_json::from_str::<GameBoardLocation>(r#"{"x":10,"y":1}"#).is_err());
+    assert!(
+        serde_json::from_str::<RuleSet>(
+            r#"{"line_size":5,"colors_count":8,"balls_per_turn":3,"starting_balls":5}"#
+        ).is_err()
+    );
+
+    let rules = RuleSet::new(5, 1, 3, 5).unwrap();
+    let mut json = serde_json::to_value(GameBoard::with_rules(rules).unwrap()).unwrap();
+    json["rows"][0][0] = "pink".into();
+    assert!(serde_json::from_value::<GameBoard>(json.clone()).is_err());
+    json["rows"][0][0] = "green".into();
+    assert!(serde_json::from_value::<GameBoard>(json.clone()).is_ok());
+    json["rows"].as_array_mut().unwrap().pop();
+    assert!(serde_json::from_value::<GameBoard>(json).is_err());
+}
+
+#[test]
+fn game_position_deserialization_rejects_uncleared_lines() {
+    let position = GamePosition::new(GameBoard::default(), GameScore::new(12)).unwrap();
+    let mut json = serde_json::to_value(&position).unwrap();
+    assert_eq!(
+        serde_json::from_value::<GamePosition>(json.clone()).unwrap(),
+        position
+    );
+    for x in 0..5 {
+        json["game_board"]["rows"][4][x] = "red".into();
+    }
+    assert!(
+        serde_json::from_value::<GamePosition>(json)
+            .unwrap_err()
+            .to_string()
+            .contains("(1, 5)")
+    );
+}
@@ -0,0 +1,318 @@
+use std::fmt;
+use std::mem;
+use std::str;
+
+use super::board::SizedGameBoard;
+use super::common::{BallColor, GameIteration, GameScore, GAME_BOARD_SIZE};
+use super::error::{GameError, SpawnError, TurnError};
+use super::location::{SizedGameBoardLocation, SizedGameBoardMove};
+use super::outcome::{GameOverReason, SizedMoveOutcome};
+use super::rules::RuleSet;
+use super::spawner::{BallSpawner, SizedNextBalls};
+
+#[cfg(test)]
+mod tests;
+
+pub type GameSession<S> = SizedGameSession<GAME_BOARD_SIZE, S>;
+pub type SpawnedBall = SizedSpawnedBall<GAME_BOARD_SIZE>;
+pub type TurnOutcome = SizedTurnOutcome<GAME_BOARD_SIZE>;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SizedSpawnedBall<const N: usize> {
+    pub location: SizedGameBoardLocation<N>,
+    pub ball_color: BallColor,
+    pub move_outcome: SizedMoveOutcome<N>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SizedTurnOutcome<const N: usize> {
+    /// The outcome of the player move (empty for the turn that starts the game).
+    pub move_outcome: SizedMoveOutcome<N>,
+    pub spawned_balls: Vec<SizedSpawnedBall<N>>,
+    /// The score gained during the turn, including the lines completed by the spawned balls.
+    pub score: GameScore,
+    pub is_game_over: bool,
+}
+
+/// Whether the balls of the next turn are decided one turn ahead, and what of them is shown.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PreviewMode {
+    #[default]
+    Disabled,
+    /// The colors of the next balls are known, their locations are picked at the spawn time.
+    Colors,
+    /// Both the colors and the locations are known; a ball whose place gets taken by the player
+    /// move is put into another location picked by the spawner.
+    ColorsAndLocations,
+}
+
+impl fmt::Display for PreviewMode {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "{}",
+            match *self {
+                PreviewMode::Disabled => "off",
+                PreviewMode::Colors => "colors",
+                PreviewMode::ColorsAndLocations => "locations",
+            }
+        )
+    }
+}
+
+impl str::FromStr for PreviewMode {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim() {
+            "off" => PreviewMode::Disabled,
+            "colors" => PreviewMode::Colors,
+            "locations" => PreviewMode::ColorsAndLocations,
+            _ => {
+                return Err(GameError::parse_error(
+                    1,
+                    1,
+                    format!(
+                        "'{}' is not a supported preview mode (off, colors or locations).",
+                        s
+                    ),
+                ))
+            }
+        })
+    }
+}
+
+/// Plays a single game by the Lines turn rules: the game starts with `starting_balls` balls,
+/// every move that does not clear a line is followed by `balls_per_turn` new balls, and the game
+/// is over once the board is full (or once it is `finish`ed for another reason).
+#[derive(Debug)]
+pub struct SizedGameSession<const N: usize, S: BallSpawner<N>> {
+    game_board: SizedGameBoard<N>,
+    game_score: GameScore,
+    game_iteration: GameIteration,
+    spawner: S,
+    preview_mode: PreviewMode,
+    next_balls: SizedNextBalls<N>,
+    game_over_reason: Option<GameOverReason>,
+}
+
+impl<const N: usize, S: BallSpawner<N>> SizedGameSession<N, S> {
+    pub fn new(rules: RuleSet, spawner: S) -> Result<Self, GameError> {
+        Ok(Self::with_state(
+            SizedGameBoard::with_rules(rules)?,
+            GameScore::new(0),
+            0,
+            spawner,
+        ))
+    }
+
+    /// Resumes a game from a saved state.
+    pub fn with_state(
+        game_board: SizedGameBoard<N>,
+        game_score: GameScore,
+        game_iteration: GameIteration,
+        spawner: S,
+    ) -> Self {
+        Self {
+            game_board,
+            game_score,
+            game_iteration,
+            spawner,
+            preview_mode: PreviewMode::Disabled,
+            next_balls: SizedNextBalls::default(),
+            game_over_reason: None,
+        }
+    }
+
+    /// Enables the preview of the next balls; `next_balls` are the ones previewed before the
+    /// session was saved (if they are empty, they are picked at the end of the next turn).
+    pub fn with_preview(mut self, preview_mode: PreviewMode, next_balls: SizedNextBalls<N>) -> Self {
+        self.preview_mode = preview_mode;
+        self.next_balls = next_balls;
+        self
+    }
+
+    /// Resumes a game that was saved after it had been over.
+    pub fn with_game_over_reason(mut self, game_over_reason: Option<GameOverReason>) -> Self {
+        self.game_over_reason = game_over_reason;
+        self
+    }
+
+    #[inline]
+    pub fn game_board(&self) -> &SizedGameBoard<N> {
+        &self.game_board
+    }
+
+    #[inline]
+    pub fn game_score(&self) -> GameScore {
+        self.game_score
+    }
+
+    #[inline]
+    pub fn game_iteration(&self) -> GameIteration {
+        self.game_iteration
+    }
+
+    #[inline]
+    pub fn rules(&self) -> &RuleSet {
+        self.game_board.rules()
+    }
+
+    #[inline]
+    pub fn spawner(&self) -> &S {
+        &self.spawner
+    }
+
+    #[inline]
+    pub fn preview_mode(&self) -> PreviewMode {
+        self.preview_mode
+    }
+
+    /// The balls to be spawned on the next turn that spawns balls.
+    #[inline]
+    pub fn next_balls(&self) -> &SizedNextBalls<N> {
+        &self.next_balls
+    }
+
+    pub fn game_over_reason(&self) -> Option<GameOverReason> {
+        self.game_over_reason.or_else(|| {
+            if self.game_board.is_full() {
+                Some(GameOverReason::NoSpace)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_over_reason().is_some()
+    }
+
+    /// Ends the game for the `game_over_reason` unless it is already over, and returns the reason
+    /// the game has ended with.
+    pub fn finish(&mut self, game_over_reason: GameOverReason) -> GameOverReason {
+        let game_over_reason = self.game_over_reason().unwrap_or(game_over_reason);
+        self.game_over_reason = Some(game_over_reason);
+        self.next_balls = SizedNextBalls::default();
+        game_over_reason
+    }
+
+    /// Spawns the starting balls of the game.
+    pub fn start(&mut self) -> Result<SizedTurnOutcome<N>, SpawnError> {
+        let starting_balls = self.rules().starting_balls();
+        self.rolling_back_on_error(|game_session| {
+            game_session.finish_turn(SizedMoveOutcome::default(), starting_balls)
+        })
+    }
+
+    /// Starts the game from the position the session was created with, so no starting balls are
+    /// spawned (only the next balls are picked if they are previewed).
+    pub fn start_from_position(&mut self) -> Result<SizedTurnOutcome<N>, SpawnError> {
+        self.rolling_back_on_error(|game_session| {
+            game_session.finish_turn(SizedMoveOutcome::default(), 0)
+        })
+    }
+
+    /// Makes the player move and, unless it cleared a line, spawns new balls. An invalid move (or
+    /// a spawner that picks a ball the board does not take) leaves the session intact; the two
+    /// are told apart, since only the former is the player's fault.
+    pub fn play(
+        &mut self,
+        game_move: &SizedGameBoardMove<N>,
+    ) -> Result<SizedTurnOutcome<N>, TurnError> {
+        if let Some(reason) = self.game_over_reason() {
+            return Err(TurnError::InvalidMove(GameError::GameIsOver { reason }));
+        }
+        self.rolling_back_on_error(|game_session| {
+            let move_outcome = game_session
+                .game_board
+                .move_ball(game_move)
+                .map_err(TurnError::InvalidMove)?;
+            let balls_to_spawn = if move_outcome.is_scored() {
+                0
+            } else {
+                game_session.rules().balls_per_turn()
+            };
+            Ok(game_session.finish_turn(move_outcome, balls_to_spawn)?)
+        })
+    }
+
+    /// Runs the turn, restoring the board and the next balls if it fails partway (the score and
+    /// the iteration are only updated once the turn has succeeded).
+    fn rolling_back_on_error<F, E>(&mut self, turn: F) -> Result<SizedTurnOutcome<N>, E>
+    where
+        F: FnOnce(&mut Self) -> Result<SizedTurnOutcome<N>, E>,
+    {
+        let game_board = self.game_board.clone();
+        let next_balls = self.next_balls.clone();
+        let turn_outcome = turn(self);
+        if turn_outcome.is_err() {
+            self.game_board = game_board;
+            self.next_balls = next_balls;
+        }
+        turn_outcome
+    }
+
+    fn finish_turn(
+        &mut self,
+        move_outcome: SizedMoveOutcome<N>,
+        balls_to_spawn: usize,
+    ) -> Result<SizedTurnOutcome<N>, SpawnError> {
+        let mut turn_score = move_outcome.score;
+        let mut spawned_balls = Vec::with_capacity(balls_to_spawn);
+        if balls_to_spawn > 0 {
+            self.spawner.begin_turn(self.game_iteration);
+            let mut next_balls = mem::take(&mut self.next_balls.0).into_iter();
+            while spawned_balls.len() < balls_to_spawn {
+                let (location, ball_color) = match next_balls.next() {
+                    Some((location, ball_color))
+                        if self.preview_mode == PreviewMode::ColorsAndLocations
+                            && self.game_board.at(&location).is_empty() =>
+                    {
+                        (location, ball_color)
+                    }
+                    Some((_, ball_color)) => match self.spawner.next_ball(&self.game_board) {
+                        Some((location, _)) => (location, ball_color),
+                        None => break,
+                    },
+                    None => match self.spawner.next_ball(&self.game_board) {
+                        Some(next_ball) => next_ball,
+                        None => break,
+                    },
+                };
+                let spawn_outcome = self.game_board
+                    .add_ball(&location, ball_color)
+                    .map_err(SpawnError)?;
+                turn_score += spawn_outcome.score;
+                spawned_balls.push(SizedSpawnedBall {
+                    location,
+                    ball_color,
+                    move_outcome: spawn_outcome,
+                });
+            }
+        }
+        self.game_score += turn_score;
+        self.game_iteration += 1;
+        let is_game_over = self.is_game_over();
+        if self.preview_mode != PreviewMode::Disabled && self.next_balls.is_empty() && !is_game_over
+        {
+            self.spawner.begin_turn(self.game_iteration);
+            for _ in 0..self.rules().balls_per_turn() {
+                match self.spawner.next_ball(&self.game_board) {
+                    Some(next_ball) => self.next_balls.0.push(next_ball),
+                    None => break,
+                }
+            }
+        }
+        Ok(SizedTurnOutcome {
+            move_outcome,
+            spawned_balls,
+            score: turn_score,
+            is_game_over,
+        })
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::VecDeque;
+
+use super::super::board::GameBoard;
+use super::super::common::{BallColor, GameIteration, GameScore};
+use super::super::error::{GameError, SpawnError, TurnError};
+use super::super::location::{GameBoardLocation, GameBoardMove};
+use super::super::outcome::GameOverReason;
+use super::super::position::GamePosition;
+use super::super::rules::RuleSet;
+use super::super::spawner::BallSpawner;
+use super::super::spawner::NextBalls;
+use super::{GameSession, PreviewMode};
+
+#[derive(Default)]
+struct ScriptedSpawner {
+    balls: VecDeque<(GameBoardLocation, BallColor)>,
+    turns: Vec<GameIteration>,
+}
+
+impl ScriptedSpawner {
+    fn new(balls: &[(usize, usize, BallColor)]) -> Self {
+        Self {
+            balls: balls
+                .iter()
+                .map(|&(x, y, ball_color)| {
+                    (GameBoardLocation::from_coords(x, y).unwrap(), ball_color)
+                })
+                .collect(),
+            turns: Vec::new(),
+        }
+    }
+}
+
+impl BallSpawner<9> for ScriptedSpawner {
+    fn begin_turn(&mut self, game_iteration: GameIteration) {
+        self.turns.push(game_iteration);
+    }
+
+    fn next_ball(&mut self, _game_board: &GameBoard) -> Option<(GameBoardLocation, BallColor)> {
+        self.balls.pop_front()
+    }
+}
+
+#[test]
+fn game_session_spawns_starting_balls_and_balls_per_turn() {
+    use super::super::common::BallColor::*;
+    let spawner = ScriptedSpawner::new(&[
+        (1, 1, Red),
+        (2, 1, Green),
+        (3, 1, Blue),
+        (4, 1, Pink),
+        (5, 1, Cyan),
+        (1, 9, Red),
+        (2, 9, Red),
+        (3, 9, Red),
+    ]);
+    let mut game_session = GameSession::new(RuleSet::default(), spawner).unwrap();
+
+    let turn_outcome = game_session.start().unwrap();
+    assert_eq!(turn_outcome.spawned_balls.len(), 5);
+    assert!(!turn_outcome.move_outcome.is_scored());
+    assert_eq!(game_session.game_iteration(), 1);
+
+    let turn_outcome = game_session
+        .play(&"1 1 1 2".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    assert_eq!(turn_outcome.spawned_balls.len(), 3);
+    assert_eq!(turn_outcome.score, GameScore::new(0));
+    assert!(!turn_outcome.is_game_over);
+    assert_eq!(game_session.game_board().balls_count(), 8);
+    assert_eq!(game_session.spawner().turns, vec![0, 1]);
+}
+
+#[test]
+fn game_session_does_not_spawn_after_clearing_a_line() {
+    let mut game_board = GameBoard::default();
+    for x in 1..5 {
+        game_board
+            .add_ball(&GameBoardLocation::from_coords(x, 1).unwrap(), BallColor::Red)
+            .unwrap();
+    }
+    game_board
+        .add_ball(&GameBoardLocation::from_coords(9, 9).unwrap(), BallColor::Red)
+        .unwrap();
+    let spawner = ScriptedSpawner::new(&[(5, 5, BallColor::Blue)]);
+    let mut game_session = GameSession::with_state(game_board, GameScore::new(3), 7, spawner);
+
+    let turn_outcome = game_session
+        .play(&"9 9 5 1".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    assert!(turn_outcome.move_outcome.is_scored());
+    assert!(turn_outcome.spawned_balls.is_empty());
+    assert_eq!(turn_outcome.score, GameScore::new(10));
+    assert_eq!(game_session.game_score(), GameScore::new(13));
+    assert_eq!(game_session.game_iteration(), 8);
+    assert!(game_session.game_board().is_empty());
+    assert!(game_session.spawner().turns.is_empty());
+}
+
+#[test]
+fn game_session_scores_lines_completed_by_spawned_balls() {
+    let mut game_board = GameBoard::default();
+    for x in 1..5 {
+        game_board
+            .add_ball(&GameBoardLocation::from_coords(x, 1).unwrap(), BallColor::Red)
+            .unwrap();
+    }
+    game_board
+        .add_ball(&GameBoardLocation::from_coords(9, 9).unwrap(), BallColor::Blue)
+        .unwrap();
+    let spawner = ScriptedSpawner::new(&[(5, 1, BallColor::Red), (5, 1, BallColor::Red)]);
+    let mut game_session = GameSession::with_state(game_board, GameScore::new(0), 1, spawner);
+
+    let turn_outcome = game_session
+        .play(&"9 9 9 8".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    assert_eq!(turn_outcome.spawned_balls.len(), 2);
+    assert!(turn_outcome.spawned_balls[0].move_outcome.is_scored());
+    assert_eq!(turn_outcome.score, GameScore::new(10));
+    assert_eq!(game_session.game_score(), GameScore::new(10));
+    assert_eq!(game_session.game_board().balls_count(), 2);
+}
+
+#[test]
+fn game_session_keeps_state_on_invalid_move() {
+    let spawner = ScriptedSpawner::new(&[(1, 1, BallColor::Red)]);
+    let mut game_session = GameSession::new(RuleSet::new(5, 7, 3, 1).unwrap(), spawner).unwrap();
+    game_session.start().unwrap();
+    let game_board_before = game_session.game_board().clone();
+
+    assert!(
+        game_session
+            .play(&"2 2 3 3".parse::<GameBoardMove>().unwrap())
+            .is_err()
+    );
+    assert_eq!(game_session.game_board(), &game_board_before);
+    assert_eq!(game_session.game_iteration(), 1);
+}
+
+#[test]
+fn game_session_keeps_state_when_spawning_fails() {
+    let spawner = ScriptedSpawner::new(&[
+        (1, 1, BallColor::Red),
+        (5, 5, BallColor::Green),
+        (3, 3, BallColor::Blue),
+    ]);
+    let mut game_session = GameSession::new(RuleSet::new(5, 7, 2, 1).unwrap(), spawner).unwrap();
+    game_session.start().unwrap();
+    let game_board_before = game_session.game_board().clone();
+
+    // The second spawned ball goes where the player has just moved the ball.
+    assert_eq!(
+        game_session.play(&"1 1 3 3".parse::<GameBoardMove>().unwrap()),
+        Err(TurnError::Spawn(SpawnError(GameError::DestinationOccupied {
+            x: 3,
+            y: 3,
+        })))
+    );
+    assert_eq!(game_session.game_board(), &game_board_before);
+    assert_eq!(game_session.game_score(), GameScore::new(0));
+    assert_eq!(game_session.game_iteration(), 1);
+}
+
+#[test]
+fn game_session_is_over_when_the_board_is_full() {
+    let mut balls = Vec::new();
+    for y in 1..10 {
+        for x in 1..10 {
+            let ball_color = if (x + 2 * y) % 4 < 2 {
+                BallColor::Red
+            } else {
+                BallColor::Green
+            };
+            balls.push((x, y, ball_color));
+        }
+    }
+    let spawner = ScriptedSpawner::new(&balls);
+    let mut game_session = GameSession::new(RuleSet::new(5, 7, 3, 81).unwrap(), spawner).unwrap();
+    let turn_outcome = game_session.start().unwrap();
+    assert_eq!(turn_outcome.spawned_balls.len(), 81);
+    assert_eq!(turn_outcome.score, GameScore::new(0));
+    assert!(turn_outcome.is_game_over);
+    assert!(game_session.is_game_over());
+    assert_eq!(game_session.game_over_reason(), Some(GameOverReason::NoSpace));
+    assert_eq!(
+        game_session.finish(GameOverReason::MoveLimit),
+        GameOverReason::NoSpace
+    );
+}
+
+#[test]
+fn game_session_can_be_finished_explicitly() {
+    let spawner = ScriptedSpawner::new(&[(1, 1, BallColor::Red), (5, 5, BallColor::Blue)]);
+    let mut game_session = GameSession::new(RuleSet::new(5, 7, 3, 1).unwrap(), spawner)
+        .unwrap()
+        .with_preview(PreviewMode::Colors, NextBalls::default());
+    game_session.start().unwrap();
+    assert!(!game_session.next_balls().is_empty());
+    assert_eq!(game_session.game_over_reason(), None);
+
+    assert_eq!(
+        game_session.finish(GameOverReason::InvalidMove),
+        GameOverReason::InvalidMove
+    );
+    assert!(game_session.is_game_over());
+    assert!(game_session.next_balls().is_empty());
+    assert_eq!(
+        game_session.finish(GameOverReason::MoveLimit),
+        GameOverReason::InvalidMove
+    );
+    assert_eq!(
+        game_session.play(&"1 1 2 2".parse::<GameBoardMove>().unwrap()),
+        Err(TurnError::InvalidMove(GameError::GameIsOver {
+            reason: GameOverReason::InvalidMove,
+        }))
+    );
+}
+
+#[test]
+fn game_over_reasons_can_be_formatted_and_parsed() {
+    for &game_over_reason in &GameOverReason::ALL {
+        assert_eq!(
+            game_over_reason.to_string().parse::<GameOverReason>(),
+            Ok(game_over_reason)
+        );
+    }
+    assert!("full".parse::<GameOverReason>().is_err());
+}
+
+#[test]
+fn preview_modes_can_be_formatted_and_parsed() {
+    for &preview_mode in &[
+        PreviewMode::Disabled,
+        PreviewMode::Colors,
+        PreviewMode::ColorsAndLocations,
+    ] {
+        assert_eq!(
+            preview_mode.to_string().parse::<PreviewMode>(),
+            Ok(preview_mode)
+        );
+    }
+    assert_eq!(PreviewMode::ColorsAndLocations.to_string(), "locations");
+}
+
+#[test]
+fn game_session_previews_colors_of_the_next_balls() {
+    use super::super::common::BallColor::*;
+    let spawner = ScriptedSpawner::new(&[
+        (1, 1, Red),
+        (2, 1, Green),
+        (1, 9, Blue),
+        (2, 9, Pink),
+        (3, 9, Cyan),
+        (5, 5, Yellow),
+    ]);
+    let mut game_session = GameSession::new(RuleSet::new(5, 7, 2, 2).unwrap(), spawner)
+        .unwrap()
+        .with_preview(PreviewMode::Colors, NextBalls::default());
+    game_session.start().unwrap();
+    assert_eq!(
+        game_session.next_balls().ball_colors().collect::<Vec<_>>(),
+        vec![Blue, Pink]
+    );
+
+    let turn_outcome = game_session
+        .play(&"1 1 1 2".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    let spawned_balls = turn_outcome
+        .spawned_balls
+        .iter()
+        .map(|spawned_ball| {
+            (
+                spawned_ball.location.x(),
+                spawned_ball.location.y(),
+                spawned_ball.ball_color,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(spawned_balls, vec![(3, 9, Blue), (5, 5, Pink)]);
+    assert!(game_session.next_balls().is_empty());
+    assert_eq!(game_session.spawner().turns, vec![0, 1, 1, 2]);
+}
+
+#[test]
+fn game_session_keeps_previewed_balls_after_clearing_a_line() {
+    let mut game_board = GameBoard::default();
+    for x in 1..5 {
+        game_board
+            .add_ball(&GameBoardLocation::from_coords(x, 1).unwrap(), BallColor::Red)
+            .unwrap();
+    }
+    game_board
+        .add_ball(&GameBoardLocation::from_coords(9, 9).unwrap(), BallColor::Red)
+        .unwrap();
+    let next_balls = "5 1 G 9 9 B 3 3 Y".parse::<NextBalls>().unwrap();
+    let spawner = ScriptedSpawner::new(&[(7, 7, BallColor::Cyan)]);
+    let mut game_session = GameSession::with_state(game_board, GameScore::new(0), 4, spawner)
+        .with_preview(PreviewMode::ColorsAndLocations, next_balls.clone());
+
+    game_session
+        .play(&"9 9 5 1".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    assert_eq!(game_session.next_balls(), &next_balls);
+    assert!(game_session.game_board().is_empty());
+}
+
+#[test]
+fn game_session_moves_previewed_ball_when_its_place_is_taken() {
+    let mut game_board = GameBoard::default();
+    game_board
+        .add_ball(&GameBoardLocation::from_coords(1, 1).unwrap(), BallColor::Red)
+        .unwrap();
+    let next_balls = "2 1 G 4 4 B".parse::<NextBalls>().unwrap();
+    let spawner = ScriptedSpawner::new(&[(7, 7, BallColor::Cyan)]);
+    let mut game_session = GameSession::with_state(game_board, GameScore::new(0), 1, spawner)
+        .with_preview(PreviewMode::ColorsAndLocations, next_balls);
+    let turn_outcome = game_session
+        .play(&"1 1 2 1".parse::<GameBoardMove>().unwrap())
+        .unwrap();
+    assert_eq!(
+        turn_outcome.spawned_balls[0].location,
+        GameBoardLocation::from_coords(7, 7).unwrap()
+    );
+    assert_eq!(turn_outcome.spawned_balls[0].ball_color, BallColor::Green);
+    assert_eq!(
+        turn_outcome.spawned_balls[1].location,
+        GameBoardLocation::from_coords(4, 4).unwrap()
+    );
+    assert_eq!(turn_outcome.spawned_balls[1].ball_color, BallColor::Blue);
+}
+
+#[test]
+fn game_session_can_start_from_a_position() {
+    let position = "\
+R R _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+
+30"
+        .parse::<GamePosition>()
+        .unwrap();
+    let spawner = ScriptedSpawner::new(&[(9, 9, BallColor::Blue)]);
+    let mut game_session = GameSession::with_state(
+        position.game_board.clone(),
+        position.game_score,
+        0,
+        spawner,
+    ).with_preview(PreviewMode::Colors, NextBalls::default());
+    let turn_outcome = game_session.start_from_position().unwrap();
+    assert!(turn_outcome.spawned_balls.is_empty());
+    assert_eq!(game_session.game_board(), &position.game_board);
+    assert_eq!(game_session.game_score(), GameScore::new(30));
+    assert_eq!(game_session.game_iteration(), 1);
+    assert_eq!(
+        game_session.next_balls().ball_colors().collect::<Vec<_>>(),
+        vec![BallColor::Blue]
+    );
+}
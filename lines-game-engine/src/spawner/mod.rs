@@ -0,0 +1,580 @@
+use std::fmt;
+use std::str;
+
+use rand;
+
+use super::board::SizedGameBoard;
+use super::common::{words_with_positions, BallColor, GameIteration, GAME_BOARD_SIZE};
+use super::error::GameError;
+use super::location::SizedGameBoardLocation;
+use super::outcome::LineDirection;
+use super::random::SpawnRng;
+use super::rules::RuleSet;
+
+#[cfg(test)]
+mod tests;
+
+pub type NextBalls = SizedNextBalls<GAME_BOARD_SIZE>;
+pub type ScriptedBallSpawner = SizedScriptedBallSpawner<GAME_BOARD_SIZE>;
+
+/// Decides where the new balls appear and which colors they have.
+pub trait BallSpawner<const N: usize> {
+    /// Called once before the balls of the `game_iteration` turn are spawned.
+    fn begin_turn(&mut self, _game_iteration: GameIteration) {}
+
+    /// Picks an empty location and a color allowed by the board rule set for the next ball, or
+    /// returns `None` when no more balls can be placed.
+    fn next_ball(
+        &mut self,
+        game_board: &SizedGameBoard<N>,
+    ) -> Option<(SizedGameBoardLocation<N>, BallColor)>;
+
+    /// The RNG the balls are drawn from, to be recorded in the game logs; `None` if the spawner
+    /// does not draw random numbers.
+    fn rng_description(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<const N: usize, S: BallSpawner<N> + ?Sized> BallSpawner<N> for &mut S {
+    fn begin_turn(&mut self, game_iteration: GameIteration) {
+        (**self).begin_turn(game_iteration)
+    }
+
+    fn next_ball(
+        &mut self,
+        game_board: &SizedGameBoard<N>,
+    ) -> Option<(SizedGameBoardLocation<N>, BallColor)> {
+        (**self).next_ball(game_board)
+    }
+
+    fn rng_description(&self) -> Option<String> {
+        (**self).rng_description()
+    }
+}
+
+impl<const N: usize, S: BallSpawner<N> + ?Sized> BallSpawner<N> for Box<S> {
+    fn begin_turn(&mut self, game_iteration: GameIteration) {
+        (**self).begin_turn(game_iteration)
+    }
+
+    fn next_ball(
+        &mut self,
+        game_board: &SizedGameBoard<N>,
+    ) -> Option<(SizedGameBoardLocation<N>, BallColor)> {
+        (**self).next_ball(game_board)
+    }
+
+    fn rng_description(&self) -> Option<String> {
+        (**self).rng_description()
+    }
+}
+
+fn random_empty_location<const N: usize>(
+    rng: &mut SpawnRng,
+    game_board: &SizedGameBoard<N>,
+) -> Option<SizedGameBoardLocation<N>> {
+    let empty_cells_count = N * N - game_board.balls_count();
+    if empty_cells_count == 0 {
+        return None;
+    }
+    game_board
+        .empty_locations()
+        .nth(rng.below(empty_cells_count))
+}
+
+/// Places the balls uniformly over the empty cells and picks their colors uniformly from the
+/// colors allowed by the rule set. The random spawners draw from `SpawnRng::for_turn(game_seed,
+/// game_iteration)`, so a turn only depends on the game seed, its iteration and the board.
+#[derive(Debug)]
+pub struct UniformBallSpawner {
+    game_seed: u64,
+    rng: SpawnRng,
+}
+
+impl UniformBallSpawner {
+    pub fn new(game_seed: u64) -> Self {
+        Self {
+            game_seed,
+            rng: SpawnRng::for_turn(game_seed, 0),
+        }
+    }
+}
+
+impl<const N: usize> BallSpawner<N> for UniformBallSpawner {
+    fn rng_description(&self) -> Option<String> {
+        Some(SpawnRng::description())
+    }
+
+    fn begin_turn(&mut self, game_iteration: GameIteration) {
+        self.rng = SpawnRng::for_turn(self.game_seed, game_iteration);
+    }
+
+    fn next_ball(
+        &mut self,
+        game_board: &SizedGameBoard<N>,
+    ) -> Option<(SizedGameBoardLocation<N>, BallColor)> {
+        let location = random_empty_location(&mut self.rng, game_board)?;
+        let ball_colors = game_board.rules().ball_colors();
+        Some((location, ball_colors[self.rng.below(ball_colors.len())]))
+    }
+}
+
+/// Places the balls uniformly over the empty cells and picks their colors proportionally to
+/// the weights (indexed by `BallColor::index()`). Colors that are not allowed by the rule set
+/// are never picked; if all the allowed colors have zero weights, the colors are uniform.
+#[derive(Debug)]
+pub struct WeightedColorBallSpawner {
+    game_seed: u64,
+    rng: SpawnRng,
+    color_weights: [u32; 7],
+}
+
+impl WeightedColorBallSpawner {
+    pub fn new(game_seed: u64, color_weights: [u32; 7]) -> Self {
+        Self {
+            game_seed,
+            rng: SpawnRng::for_turn(game_seed, 0),
+            color_weights,
+        }
+    }
+}
+
+impl<const N: usize> BallSpawner<N> for WeightedColorBallSpawner {
+    fn rng_description(&self) -> Option<String> {
+        Some(SpawnRng::description())
+    }
+
+    fn begin_turn(&mut self, game_iteration: GameIteration) {
+        self.rng = SpawnRng::for_turn(self.game_seed, game_iteration);
+    }
+
+    fn next_ball(
+        &mut self,
+        game_board: &SizedGameBoard<N>,
+    ) -> Option<(SizedGameBoardLocation<N>, BallColor)> {
+        let location = random_empty_location(&mut self.rng, game_board)?;
+        let ball_colors = game_board.rules().ball_colors();
+        // The sum of seven `u32` weights always fits `u64`.
+        let total_weight: u64 = self.color_weights[..ball_colors.len()]
+            .iter()
+            .map(|&color_weight| u64::from(color_weight))
+            .sum();
+        if total_weight == 0 {
+            return Some((location, ball_colors[self.rng.below(ball_colors.len())]));
+        }
+        let mut weight_point = self.rng.below_u64(total_weight);
+        for &ball_color in ball_colors {
+            let color_weight = u64::from(self.color_weights[ball_color.index()]);
+            if weight_point < color_weight {
+                return Some((location, ball_color));
+            }
+            weight_point -= color_weight;
+        }
+        unreachable!("The weight point is always below the total weight.");
+    }
+}
+
+/// Replays the balls listed in a script. Every line of the script lists the balls of the turn
+/// with the same game iteration as `x y C` triples (1-based coordinates and a color code), e.g.
+/// `1 1 R 5 7 G`. The balls whose places are already taken are skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizedScriptedBallSpawner<const N: usize> {
+    turns: Vec<Vec<(SizedGameBoardLocation<N>, BallColor)>>,
+    next_balls: Vec<(SizedGameBoardLocation<N>, BallColor)>,
+}
+
+impl<const N: usize> SizedScriptedBallSpawner<N> {
+    pub fn new(turns: Vec<Vec<(SizedGameBoardLocation<N>, BallColor)>>) -> Self {
+        Self {
+            turns,
+            next_balls: Vec::new(),
+        }
+    }
+
+    /// Parses the script, rejecting the balls of the colors that are not allowed by the rule set.
+    pub fn parse_with_rules(s: &str, rules: RuleSet) -> Result<Self, GameError> {
+        Ok(Self::new(
+            s.lines()
+                .enumerate()
+                .map(|(line_index, line)| parse_balls_line(line, line_index + 1, &rules))
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+}
+
+impl<const N: usize> BallSpawner<N> for SizedScriptedBallSpawner<N> {
+    fn begin_turn(&mut self, game_iteration: GameIteration) {
+        self.next_balls = self.turns
+            .get(game_iteration as usize)
+            .cloned()
+            .unwrap_or_default();
+        self.next_balls.reverse();
+    }
+
+    fn next_ball(
+        &mut self,
+        game_board: &SizedGameBoard<N>,
+    ) -> Option<(SizedGameBoardLocation<N>, BallColor)> {
+        while let Some((location, ball_color)) = self.next_balls.pop() {
+            if game_board.at(&location).is_empty() {
+                return Some((location, ball_color));
+            }
+        }
+        None
+    }
+}
+
+impl<const N: usize> str::FromStr for SizedScriptedBallSpawner<N> {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_rules(s, RuleSet::default())
+    }
+}
+
+fn parse_balls_line<const N: usize>(
+    line: &str,
+    line_number: usize,
+    rules: &RuleSet,
+) -> Result<Vec<(SizedGameBoardLocation<N>, BallColor)>, GameError> {
+    let words = words_with_positions(line, char::is_whitespace);
+    let mut balls = Vec::with_capacity(words.len() / 3);
+    for ball_words in words.chunks(3) {
+        let (_, column, _) = ball_words[0];
+        if ball_words.len() < 3 {
+            return Err(GameError::parse_error(
+                line_number,
+                column,
+                "Every ball is expected to be an `x y C` triple.",
+            ));
+        }
+        let mut coords = [0; 2];
+        for (coord, &(_, column, word)) in coords.iter_mut().zip(ball_words) {
+            *coord = word.parse::<usize>().map_err(|_| {
+                GameError::parse_error(
+                    line_number,
+                    column,
+                    format!("'{}' is not a valid coordinate.", word),
+                )
+            })?;
+        }
+        let (_, color_column, color_word) = ball_words[2];
+        let mut color_chars = color_word.chars();
+        let ball_color = match (color_chars.next(), color_chars.next()) {
+            (Some(color_char), None) => BallColor::from_char(color_char).ok(),
+            _ => None,
+        }.ok_or_else(|| {
+            GameError::parse_error(
+                line_number,
+                color_column,
+                format!("'{}' is not a supported color code.", color_word),
+            )
+        })?;
+        if !rules.is_color_allowed(ball_color) {
+            return Err(GameError::parse_error(
+                line_number,
+                color_column,
+                GameError::ColorNotAllowed {
+                    ball_color,
+                    colors_count: rules.colors_count(),
+                },
+            ));
+        }
+        balls.push((
+            SizedGameBoardLocation::from_coords(coords[0], coords[1])?,
+            ball_color,
+        ));
+    }
+    Ok(balls)
+}
+
+/// The balls decided ahead of the turn they are spawned on; written as `x y C` triples in the
+/// same way as the lines of a ball script.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SizedNextBalls<const N: usize>(pub Vec<(SizedGameBoardLocation<N>, BallColor)>);
+
+impl<const N: usize> SizedNextBalls<N> {
+    pub fn ball_colors(&self) -> impl Iterator<Item = BallColor> + '_ {
+        self.0.iter().map(|&(_, ball_color)| ball_color)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<const N: usize> fmt::Display for SizedNextBalls<N> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        for (index, &(location, ball_color)) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(fmt, " ")?;
+            }
+            write!(
+                fmt,
+                "{} {} {}",
+                location.x(),
+                location.y(),
+                ball_color.to_char()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> str::FromStr for SizedNextBalls<N> {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut balls = Vec::new();
+        for (line_index, line) in s.lines().enumerate() {
+            balls.extend(parse_balls_line(line, line_index + 1, &RuleSet::default())?);
+        }
+        Ok(SizedNextBalls(balls))
+    }
+}
+
+/// Tries to spoil the player lines: every ball is put into the empty cell that would extend the
+/// longest line of a single color, and it gets the color that makes the shortest line there.
+/// The ties are broken randomly.
+#[derive(Debug)]
+pub struct AdversarialBallSpawner {
+    game_seed: u64,
+    rng: SpawnRng,
+}
+
+impl AdversarialBallSpawner {
+    pub fn new(game_seed: u64) -> Self {
+        Self {
+            game_seed,
+            rng: SpawnRng::for_turn(game_seed, 0),
+        }
+    }
+}
+
+/// The length of the longest line that a `ball_color` ball would form in the `location`.
+fn line_length_through<const N: usize>(
+    game_board: &SizedGameBoard<N>,
+    location: &SizedGameBoardLocation<N>,
+    ball_color: BallColor,
+) -> usize {
+    LineDirection::ALL
+        .iter()
+        .map(|direction| {
+            let (dx, dy) = direction.delta();
+            let same_color_balls = |dx, dy| {
+                location
+                    .walk(dx, dy)
+                    .skip(1)
+                    .take_while(|location| game_board.at(location).0 == Some(ball_color))
+                    .count()
+            };
+            1 + same_color_balls(dx, dy) + same_color_balls(-dx, -dy)
+        })
+        .max()
+        .unwrap_or(1)
+}
+
+impl<const N: usize> BallSpawner<N> for AdversarialBallSpawner {
+    fn rng_description(&self) -> Option<String> {
+        Some(SpawnRng::description())
+    }
+
+    fn begin_turn(&mut self, game_iteration: GameIteration) {
+        self.rng = SpawnRng::for_turn(self.game_seed, game_iteration);
+    }
+
+    fn next_ball(
+        &mut self,
+        game_board: &SizedGameBoard<N>,
+    ) -> Option<(SizedGameBoardLocation<N>, BallColor)> {
+        let ball_colors = game_board.rules().ball_colors();
+        let mut best_locations = Vec::new();
+        let mut best_threat = 0;
+        for location in game_board.empty_locations() {
+            let threat = ball_colors
+                .iter()
+                .map(|&ball_color| line_length_through(game_board, &location, ball_color))
+                .max()
+                .unwrap_or(1);
+            if threat > best_threat {
+                best_threat = threat;
+                best_locations.clear();
+            }
+            if threat == best_threat {
+                best_locations.push(location);
+            }
+        }
+        let location = *self.rng.choose(&best_locations)?;
+
+        let mut best_colors = Vec::new();
+        let mut best_line_length = usize::MAX;
+        for &ball_color in ball_colors {
+            let line_length = line_length_through(game_board, &location, ball_color);
+            if line_length < best_line_length {
+                best_line_length = line_length;
+                best_colors.clear();
+            }
+            if line_length == best_line_length {
+                best_colors.push(ball_color);
+            }
+        }
+        let ball_color = *self.rng.choose(&best_colors)?;
+        Some((location, ball_color))
+    }
+}
+
+/// The version of the `LegacyBallSpawner` sampling algorithm.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum SpawnVersion {
+    /// The original lines-generator algorithm: the locations are drawn from a clone of the RNG
+    /// until an empty one is hit, while every attempt also draws a color from the RNG itself.
+    /// It is the default, so the historical game logs are replayed as they were.
+    #[default]
+    RejectionSampling,
+    /// The balls are spawned by `UniformBallSpawner` from the versioned `SpawnRng`: one draw for
+    /// the location, uniform over the empty cells, and one for the color, so a turn always
+    /// finishes.
+    UniformOverEmptyCells,
+}
+
+impl SpawnVersion {
+    pub fn number(self) -> u32 {
+        match self {
+            SpawnVersion::RejectionSampling => 1,
+            SpawnVersion::UniformOverEmptyCells => 2,
+        }
+    }
+}
+
+impl fmt::Display for SpawnVersion {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.number())
+    }
+}
+
+impl str::FromStr for SpawnVersion {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim() {
+            "1" => SpawnVersion::RejectionSampling,
+            "2" => SpawnVersion::UniformOverEmptyCells,
+            _ => {
+                return Err(GameError::parse_error(
+                    1,
+                    1,
+                    format!("'{}' is not a supported spawn version (1 or 2).", s),
+                ))
+            }
+        })
+    }
+}
+
+/// Spawns the balls the way the original lines-generator did (by default, `SpawnVersion` 1) or
+/// uniformly over the empty cells (`SpawnVersion` 2).
+///
+/// Version 1 draws from the rand 0.4 IsaacRng re-seeded with the game iteration on every turn, so
+/// the historical game logs are replayed as they were. Version 2 is a `UniformBallSpawner` seeded
+/// with 0, so both versions are predictable from the game iteration alone. A `salted` spawner
+/// takes its seeds from `SpawnRng::for_turn(game_seed, game_iteration)` instead.
+#[derive(Debug)]
+pub struct LegacyBallSpawner {
+    version: SpawnVersion,
+    game_seed: Option<u64>,
+    rng: rand::IsaacRng,
+    locations_rng: rand::IsaacRng,
+    uniform_spawner: UniformBallSpawner,
+}
+
+impl LegacyBallSpawner {
+    pub fn new() -> Self {
+        Self::with_version(SpawnVersion::default())
+    }
+
+    pub fn with_version(version: SpawnVersion) -> Self {
+        let rng = rand::IsaacRng::new_unseeded();
+        Self {
+            version,
+            game_seed: None,
+            locations_rng: rng,
+            rng,
+            uniform_spawner: UniformBallSpawner::new(0),
+        }
+    }
+
+    pub fn salted(self, game_seed: u64) -> Self {
+        Self {
+            game_seed: Some(game_seed),
+            uniform_spawner: UniformBallSpawner::new(game_seed),
+            ..self
+        }
+    }
+
+    pub fn version(&self) -> SpawnVersion {
+        self.version
+    }
+}
+
+impl Default for LegacyBallSpawner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> BallSpawner<N> for LegacyBallSpawner {
+    fn rng_description(&self) -> Option<String> {
+        Some(match (self.version, self.game_seed) {
+            (SpawnVersion::UniformOverEmptyCells, _) => SpawnRng::description(),
+            (SpawnVersion::RejectionSampling, None) => {
+                "rand 0.4 IsaacRng seeded with the game iteration".to_string()
+            }
+            (SpawnVersion::RejectionSampling, Some(_)) => format!(
+                "rand 0.4 IsaacRng seeded from {}",
+                SpawnRng::description()
+            ),
+        })
+    }
+
+    fn begin_turn(&mut self, game_iteration: GameIteration) {
+        use rand::SeedableRng;
+        if self.version == SpawnVersion::UniformOverEmptyCells {
+            BallSpawner::<N>::begin_turn(&mut self.uniform_spawner, game_iteration);
+            return;
+        }
+        let mut seed = [game_iteration; 32];
+        if let Some(game_seed) = self.game_seed {
+            let mut turn_rng = SpawnRng::for_turn(game_seed, game_iteration);
+            for seed_word in &mut seed {
+                *seed_word = turn_rng.next_u64() as u32;
+            }
+        }
+        self.rng = rand::IsaacRng::from_seed(&seed);
+        self.locations_rng = self.rng;
+    }
+
+    fn next_ball(
+        &mut self,
+        game_board: &SizedGameBoard<N>,
+    ) -> Option<(SizedGameBoardLocation<N>, BallColor)> {
+        use rand::Rng;
+        if self.version == SpawnVersion::UniformOverEmptyCells {
+            return self.uniform_spawner.next_ball(game_board);
+        }
+        let ball_colors = game_board.rules().ball_colors();
+        loop {
+            let location = SizedGameBoardLocation::from_offsets(
+                self.locations_rng.gen_range(0, N),
+                self.locations_rng.gen_range(0, N),
+            ).ok()?;
+            let ball_color = ball_colors[self.rng.gen_range(0, ball_colors.len() as i32) as usize];
+            if game_board.at(&location).is_empty() {
+                return Some((location, ball_color));
+            }
+            if game_board.is_full() {
+                return None;
+            }
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use super::super::board::GameBoard;
+use super::super::common::BallColor;
+use super::super::error::GameError;
+use super::super::location::GameBoardLocation;
+use super::super::random::SpawnRng;
+use super::super::rules::RuleSet;
+use super::{AdversarialBallSpawner, BallSpawner, LegacyBallSpawner, NextBalls, ScriptedBallSpawner,
+            SpawnVersion, UniformBallSpawner, WeightedColorBallSpawner};
+
+fn fill_game_board<S: BallSpawner<9>>(spawner: &mut S, game_board: &mut GameBoard) -> usize {
+    let mut spawned_balls_count = 0;
+    spawner.begin_turn(0);
+    while let Some((location, ball_color)) = spawner.next_ball(game_board) {
+        assert!(game_board.rules().is_color_allowed(ball_color));
+        game_board.add_ball(&location, ball_color).unwrap();
+        spawned_balls_count += 1;
+    }
+    spawned_balls_count
+}
+
+#[test]
+fn random_spawners_fill_the_board_and_stop() {
+    let rules = RuleSet::new(5, 3, 3, 5).unwrap();
+    let mut spawners: Vec<Box<dyn BallSpawner<9>>> = vec![
+        Box::new(UniformBallSpawner::new(0)),
+        Box::new(WeightedColorBallSpawner::new(0, [1, 0, 5, 0, 0, 0, 9])),
+        Box::new(AdversarialBallSpawner::new(0)),
+        Box::new(LegacyBallSpawner::new()),
+        Box::new(LegacyBallSpawner::with_version(SpawnVersion::UniformOverEmptyCells)),
+    ];
+    for spawner in &mut spawners {
+        let mut game_board = GameBoard::with_rules(rules).unwrap();
+        assert!(fill_game_board(spawner, &mut game_board) >= 81);
+        assert!(game_board.is_full());
+        assert_eq!(spawner.next_ball(&game_board), None);
+    }
+}
+
+#[test]
+fn weighted_color_spawner_only_picks_weighted_colors() {
+    let mut spawner =
+        WeightedColorBallSpawner::new(0, [0, 3, 0, 1, 0, 0, 0]);
+    let game_board = GameBoard::default();
+    for _ in 0..100 {
+        let (_, ball_color) = spawner.next_ball(&game_board).unwrap();
+        assert!(ball_color == BallColor::Pink || ball_color == BallColor::Maroon);
+    }
+}
+
+#[test]
+fn weighted_color_spawner_takes_large_weights() {
+    let mut spawner =
+        WeightedColorBallSpawner::new(0, [4_000_000_000, 0, 1_000_000_000, 0, 0, 0, u32::MAX]);
+    let game_board = GameBoard::default();
+    for _ in 0..100 {
+        let (_, ball_color) = spawner.next_ball(&game_board).unwrap();
+        assert!(ball_color != BallColor::Pink && ball_color != BallColor::Maroon);
+    }
+}
+
+#[test]
+fn scripted_spawner_replays_balls_of_the_turn() {
+    let mut spawner = "1 1 R 2 2 G\n\n9 9 B 1 1 Y 3 3 C"
+        .parse::<ScriptedBallSpawner>()
+        .unwrap();
+    let mut game_board = GameBoard::default();
+    spawner.begin_turn(0);
+    let (location, ball_color) = spawner.next_ball(&game_board).unwrap();
+    assert_eq!(location, GameBoardLocation::from_coords(1, 1).unwrap());
+    assert_eq!(ball_color, BallColor::Red);
+    game_board.add_ball(&location, ball_color).unwrap();
+    assert_eq!(
+        spawner.next_ball(&game_board),
+        Some((GameBoardLocation::from_coords(2, 2).unwrap(), BallColor::Green))
+    );
+    assert_eq!(spawner.next_ball(&game_board), None);
+
+    spawner.begin_turn(1);
+    assert_eq!(spawner.next_ball(&game_board), None);
+
+    spawner.begin_turn(2);
+    assert_eq!(
+        spawner.next_ball(&game_board),
+        Some((GameBoardLocation::from_coords(9, 9).unwrap(), BallColor::Blue))
+    );
+    assert_eq!(
+        spawner.next_ball(&game_board),
+        Some((GameBoardLocation::from_coords(3, 3).unwrap(), BallColor::Cyan))
+    );
+
+    spawner.begin_turn(3);
+    assert_eq!(spawner.next_ball(&game_board), None);
+}
+
+#[test]
+fn scripted_spawner_parse_errors_point_to_the_ball() {
+    match "1 1 R\n2 2".parse::<ScriptedBallSpawner>() {
+        Err(GameError::ParseError { line, column, .. }) => assert_eq!((line, column), (2, 1)),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    match "1 1 R 2 x G".parse::<ScriptedBallSpawner>() {
+        Err(GameError::ParseError { line, column, .. }) => assert_eq!((line, column), (1, 9)),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    match "1 1 RG".parse::<ScriptedBallSpawner>() {
+        Err(GameError::ParseError { line, column, .. }) => assert_eq!((line, column), (1, 5)),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    assert!("10 1 R".parse::<ScriptedBallSpawner>().is_err());
+}
+
+#[test]
+fn scripted_spawner_rejects_colors_not_allowed_by_the_rules() {
+    let rules = RuleSet::new(5, 5, 3, 5).unwrap();
+    assert!(ScriptedBallSpawner::parse_with_rules("1 1 R 2 2 C", rules).is_ok());
+    match ScriptedBallSpawner::parse_with_rules("1 1 R\n2 2 G 3 3 Y", rules) {
+        Err(GameError::ParseError { line, column, .. }) => assert_eq!((line, column), (2, 11)),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn adversarial_spawner_blocks_the_longest_line() {
+    let mut game_board = GameBoard::default();
+    for x in 2..6 {
+        game_board
+            .add_ball(&GameBoardLocation::from_coords(x, 5).unwrap(), BallColor::Red)
+            .unwrap();
+    }
+    game_board
+        .add_ball(&GameBoardLocation::from_coords(1, 5).unwrap(), BallColor::Green)
+        .unwrap();
+    let mut spawner = AdversarialBallSpawner::new(0);
+    let (location, ball_color) = spawner.next_ball(&game_board).unwrap();
+    assert_eq!(location, GameBoardLocation::from_coords(6, 5).unwrap());
+    assert_ne!(ball_color, BallColor::Red);
+}
+
+#[test]
+fn random_spawners_depend_only_on_game_seed_and_iteration() {
+    let game_board = GameBoard::default();
+    let mut spawner = UniformBallSpawner::new(42);
+    BallSpawner::<9>::begin_turn(&mut spawner, 7);
+    let first_ball = spawner.next_ball(&game_board).unwrap();
+    // `SpawnRng::for_turn(42, 7).below(81)` is 66, i.e. the 4th cell of the 8th row.
+    assert_eq!(first_ball.0, GameBoardLocation::from_coords(4, 8).unwrap());
+
+    BallSpawner::<9>::begin_turn(&mut spawner, 8);
+    spawner.next_ball(&game_board).unwrap();
+    BallSpawner::<9>::begin_turn(&mut spawner, 7);
+    assert_eq!(spawner.next_ball(&game_board), Some(first_ball));
+
+    let mut other_spawner = UniformBallSpawner::new(43);
+    BallSpawner::<9>::begin_turn(&mut other_spawner, 7);
+    assert_ne!(other_spawner.next_ball(&game_board), Some(first_ball));
+}
+
+#[test]
+fn legacy_spawner_is_reproducible() {
+    let game_board = GameBoard::default();
+    let mut first_spawner = LegacyBallSpawner::new();
+    let mut second_spawner = LegacyBallSpawner::new();
+    BallSpawner::<9>::begin_turn(&mut first_spawner, 42);
+    BallSpawner::<9>::begin_turn(&mut second_spawner, 42);
+    for _ in 0..10 {
+        assert_eq!(
+            first_spawner.next_ball(&game_board),
+            second_spawner.next_ball(&game_board)
+        );
+    }
+}
+
+#[test]
+fn legacy_spawner_versions_replay_their_golden_balls() {
+    assert_eq!(LegacyBallSpawner::new().version(), SpawnVersion::RejectionSampling);
+    for &(version, ref expected_balls) in &[
+        // The original lines-generator started its first game with these balls.
+        (
+            SpawnVersion::RejectionSampling,
+            [(4, 5, 'G'), (2, 4, 'M'), (3, 6, 'B'), (7, 6, 'M'), (1, 1, 'P')],
+        ),
+        (
+            SpawnVersion::UniformOverEmptyCells,
+            [(8, 9, 'M'), (5, 2, 'P'), (6, 2, 'G'), (6, 7, 'P'), (1, 1, 'Y')],
+        ),
+    ] {
+        let mut game_board = GameBoard::default();
+        let mut spawner = LegacyBallSpawner::with_version(version);
+        BallSpawner::<9>::begin_turn(&mut spawner, 0);
+        let balls = (0..5)
+            .map(|_| {
+                let (location, ball_color) = spawner.next_ball(&game_board).unwrap();
+                game_board.add_ball(&location, ball_color).unwrap();
+                (location.x(), location.y(), ball_color.to_char())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(&balls[..], &expected_balls[..], "{}", version);
+    }
+}
+
+#[test]
+fn salted_legacy_spawner_depends_on_the_salt() {
+    fn spawned_balls(
+        spawner: &mut LegacyBallSpawner,
+    ) -> Vec<Option<(GameBoardLocation, BallColor)>> {
+        let game_board = GameBoard::default();
+        BallSpawner::<9>::begin_turn(spawner, 42);
+        (0..10).map(|_| spawner.next_ball(&game_board)).collect()
+    }
+
+    let salted_balls = spawned_balls(&mut LegacyBallSpawner::new().salted(1));
+    assert_eq!(spawned_balls(&mut LegacyBallSpawner::new().salted(1)), salted_balls);
+    assert_ne!(spawned_balls(&mut LegacyBallSpawner::new().salted(2)), salted_balls);
+    assert_ne!(spawned_balls(&mut LegacyBallSpawner::new()), salted_balls);
+}
+
+#[test]
+fn next_balls_can_be_formatted_and_parsed() {
+    let next_balls = "1 2 R  9 9 G\n3 4 B".parse::<NextBalls>().unwrap();
+    assert_eq!(next_balls.0.len(), 3);
+    assert_eq!(next_balls.to_string(), "1 2 R 9 9 G 3 4 B");
+    assert_eq!(next_balls.to_string().parse::<NextBalls>().unwrap(), next_balls);
+    assert_eq!(
+        next_balls.ball_colors().collect::<Vec<_>>(),
+        vec![BallColor::Red, BallColor::Green, BallColor::Blue]
+    );
+    assert!("".parse::<NextBalls>().unwrap().is_empty());
+    assert!("1 2".parse::<NextBalls>().is_err());
+}
+
+#[test]
+fn spawners_describe_the_rng_they_draw_from() {
+    let spawn_rng_description = Some(SpawnRng::description());
+    let spawners: Vec<Box<dyn BallSpawner<9>>> = vec![
+        Box::new(UniformBallSpawner::new(1)),
+        Box::new(WeightedColorBallSpawner::new(1, [1; 7])),
+        Box::new(AdversarialBallSpawner::new(1)),
+        Box::new(LegacyBallSpawner::with_version(SpawnVersion::UniformOverEmptyCells)),
+        Box::new(LegacyBallSpawner::with_version(SpawnVersion::UniformOverEmptyCells).salted(1)),
+    ];
+    for spawner in &spawners {
+        assert_eq!(spawner.rng_description(), spawn_rng_description);
+    }
+    assert_eq!(
+        BallSpawner::<9>::rng_description(&LegacyBallSpawner::new()),
+        Some("rand 0.4 IsaacRng seeded with the game iteration".to_string())
+    );
+    assert_eq!(
+        BallSpawner::<9>::rng_description(&LegacyBallSpawner::new().salted(1)),
+        Some(format!("rand 0.4 IsaacRng seeded from {}", SpawnRng::description()))
+    );
+    assert_eq!(
+        BallSpawner::<9>::rng_description(&"".parse::<ScriptedBallSpawner>().unwrap()),
+        None
+    );
+}
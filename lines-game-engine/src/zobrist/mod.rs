@@ -0,0 +1,33 @@
+use super::common::BallColor;
+use super::random::splitmix64_next;
+
+#[cfg(test)]
+mod tests;
+
+const CELLS_COUNT: usize = 256;
+const COLORS_COUNT: usize = 7;
+
+/// The keys are derived with SplitMix64 from a fixed seed, so hashes are stable across runs and
+/// platforms.
+static ZOBRIST_KEYS: [[u64; COLORS_COUNT]; CELLS_COUNT] = generate_zobrist_keys();
+
+const fn generate_zobrist_keys() -> [[u64; COLORS_COUNT]; CELLS_COUNT] {
+    let mut keys = [[0; COLORS_COUNT]; CELLS_COUNT];
+    let mut state: u64 = 0x4c49_4e45_535f_4b48;
+    let mut cell_index = 0;
+    while cell_index < CELLS_COUNT {
+        let mut color_index = 0;
+        while color_index < COLORS_COUNT {
+            keys[cell_index][color_index] = splitmix64_next(&mut state);
+            color_index += 1;
+        }
+        cell_index += 1;
+    }
+    keys
+}
+
+/// The key of a `ball_color` ball placed at the given bit board cell index.
+#[inline]
+pub fn zobrist_key(cell_index: usize, ball_color: BallColor) -> u64 {
+    ZOBRIST_KEYS[cell_index][ball_color.index()]
+}
@@ -0,0 +1,19 @@
+use std::collections::HashSet;
+
+use super::super::common::BallColor;
+use super::zobrist_key;
+
+#[test]
+fn zobrist_keys_are_unique() {
+    let mut keys = HashSet::new();
+    for cell_index in 0..256 {
+        for &ball_color in &BallColor::ALL {
+            assert!(keys.insert(zobrist_key(cell_index, ball_color)));
+        }
+    }
+}
+
+#[test]
+fn zobrist_keys_are_stable() {
+    assert_eq!(zobrist_key(0, BallColor::Green), 0x979b_3a5f_624a_e65a);
+}
@@ -1,199 +1,410 @@
 use std::env;
+use std::fmt;
 use std::fs;
-use std::io::{Read, Write};
+use std::io::Read;
 use std::path::PathBuf;
+use std::str;
+
+use failure::ResultExt;
 
 extern crate env_logger;
 #[macro_use]
+extern crate failure;
+#[macro_use]
 extern crate log;
-extern crate rand;
-use rand::SeedableRng;
+#[macro_use]
+extern crate serde;
+extern crate serde_json;
 
 extern crate lines_game_engine;
-use lines_game_engine::{BallColor, GameBoard, GameBoardLocation, GameBoardMove, GameScore,
-                        GAME_BOARD_SIZE};
+use lines_game_engine::{salted_seed, secret_fingerprint, AdversarialBallSpawner, BallColor,
+                        BallSpawner, GameBoard, GameBoardMove, GameIteration, GameOverReason,
+                        GamePosition, GameSession, LegacyBallSpawner, NextBalls, PreviewMode,
+                        RuleSet, ScriptedBallSpawner, SpawnRng, SpawnVersion, TurnError,
+                        UniformBallSpawner, WeightedColorBallSpawner, GAME_BOARD_SIZE,
+                        GAME_OVER_PREFIX};
 
-type GameIteration = u32;
+mod state;
+#[cfg(test)]
+mod testing;
+#[cfg(test)]
+mod tests;
+use self::state::{GameRecord, GeneratorState, GeneratorStateLock};
 
-struct RandomGameBoardLocationIterator<T: rand::Rng + Clone> {
-    rng: T,
+/// Builds the ball spawner from its description: `uniform` (the default), `legacy`,
+/// `weighted:G=5,R=1,...`, `adversarial` or `scripted:<path to the script file>`. The legacy
+/// spawner keeps its original (predictable) seeds unless a secret is given. The scripted balls
+/// must have the colors allowed by the `rules`.
+fn ball_spawner(
+    ball_spawner_description: &str,
+    rules: RuleSet,
+    spawn_version: SpawnVersion,
+    game_seed: u64,
+    is_salted: bool,
+) -> Result<Box<dyn BallSpawner<GAME_BOARD_SIZE>>, failure::Error> {
+    let mut description_parts = ball_spawner_description.splitn(2, ':');
+    Ok(match (description_parts.next(), description_parts.next()) {
+        (Some("legacy"), None) => {
+            let spawner = LegacyBallSpawner::with_version(spawn_version);
+            Box::new(if is_salted {
+                spawner.salted(game_seed)
+            } else {
+                spawner
+            })
+        }
+        (Some("uniform"), None) => Box::new(UniformBallSpawner::new(game_seed)),
+        (Some("adversarial"), None) => Box::new(AdversarialBallSpawner::new(game_seed)),
+        (Some("weighted"), Some(color_weights_str)) => {
+            let mut color_weights = [0; 7];
+            for color_weight_str in color_weights_str.split(',') {
+                let mut color_weight_parts = color_weight_str.splitn(2, '=');
+                let ball_color = color_weight_parts
+                    .next()
+                    .and_then(|color_str| color_str.trim().chars().next())
+                    .and_then(|color_char| BallColor::from_char(color_char).ok());
+                let weight = color_weight_parts
+                    .next()
+                    .and_then(|weight_str| weight_str.trim().parse::<u32>().ok());
+                match (ball_color, weight) {
+                    (Some(ball_color), Some(weight)) => color_weights[ball_color.index()] = weight,
+                    _ => bail!(
+                        "BALL_SPAWNER color weights are expected to be in C=weight form, but \
+                         '{}' is found",
+                        color_weight_str
+                    ),
+                }
+            }
+            Box::new(WeightedColorBallSpawner::new(game_seed, color_weights))
+        }
+        (Some("scripted"), Some(script_filepath)) => {
+            let mut script = String::new();
+            fs::File::open(script_filepath)
+                .and_then(|mut file| file.read_to_string(&mut script))
+                .with_context(|_| {
+                    format!(
+                        "The ball spawner script file {:?} could not be read",
+                        script_filepath
+                    )
+                })?;
+            Box::new(
+                ScriptedBallSpawner::parse_with_rules(&script, rules).with_context(|_| {
+                    format!("The ball spawner script {:?} is invalid", script_filepath)
+                })?,
+            )
+        }
+        _ => bail!(
+            "BALL_SPAWNER '{}' is not supported",
+            ball_spawner_description
+        ),
+    })
 }
 
-impl<T: rand::Rng + Clone> RandomGameBoardLocationIterator<T> {
-    fn new(rng: &T) -> Self {
-        Self { rng: rng.clone() }
+/// Refuses to go on with a setting that differs from the one the game was started with; a setting
+/// the state file has not recorded yet is accepted.
+fn ensure_unchanged<T: PartialEq + fmt::Display>(
+    name: &str,
+    value: &T,
+    recorded_value: &Option<T>,
+) -> Result<(), failure::Error> {
+    match *recorded_value {
+        Some(ref recorded_value) if recorded_value != value => bail!(
+            "{} ({}) differs from the value the game was started with ({})",
+            name,
+            value,
+            recorded_value
+        ),
+        _ => Ok(()),
     }
 }
 
-impl<T: rand::Rng + Clone> std::iter::Iterator for RandomGameBoardLocationIterator<T> {
-    type Item = GameBoardLocation;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        Self::Item::from_offsets(
-            self.rng.gen_range(0, GAME_BOARD_SIZE),
-            self.rng.gen_range(0, GAME_BOARD_SIZE),
-        ).ok()
+/// Parses the environment variable if it is set.
+fn parse_env_var<T>(name: &str, expectation: &str) -> Result<Option<T>, failure::Error>
+where
+    T: str::FromStr,
+    T::Err: fmt::Display,
+{
+    match env::var(name) {
+        Ok(str_value) => str_value.parse::<T>().map(Some).map_err(|why| {
+            format_err!(
+                "{} is expected to be {}, but '{}' is given: {}",
+                name,
+                expectation,
+                str_value,
+                why
+            )
+        }),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(why) => bail!("{} could not be read: {}", name, why),
     }
 }
 
-fn random_ball_color<T: rand::Rng>(rng: &mut T) -> BallColor {
-    use BallColor::*;
-    match rng.gen_range(0, 7) {
-        0 => Green,
-        1 => Pink,
-        2 => Red,
-        3 => Maroon,
-        4 => Cyan,
-        5 => Blue,
-        6 => Yellow,
-        x => panic!("Unexpected value ({}) for a ball color", x),
-    }
-}
+fn main() {
+    env_logger::init();
 
-fn load_shared_generator_context(
-    shared_generator_context_filepath: &PathBuf,
-) -> (GameIteration, GameScore, GameBoard) {
-    if !shared_generator_context_filepath.exists() {
-        (0, GameScore::new(0), GameBoard::default())
-    } else {
-        let mut shared_generator_context_str = String::new();
-        fs::File::open(&shared_generator_context_filepath)
-            .expect("Shared generator context file should be readable.")
-            .read_to_string(&mut shared_generator_context_str)
-            .expect("Shared generator context file should be readable.");
-        let mut shared_generator_context_lines = shared_generator_context_str.lines();
-        (
-            shared_generator_context_lines
-                .next()
-                .expect("Unexpected EOF on Game Iteration value.")
-                .parse::<GameIteration>()
-                .expect("Game Iteration is expected to be an integer."),
-            shared_generator_context_lines
-                .next()
-                .expect("Unexpected EOF on Game Score value.")
-                .parse::<GameScore>()
-                .expect("Game Score is expected to be an integer."),
-            shared_generator_context_lines
-                .take(GAME_BOARD_SIZE)
-                .collect::<Vec<&str>>()
-                .join("\n")
-                .parse::<GameBoard>()
-                .expect("Game Board is expected to parse fine."),
-        )
+    if let Err(why) = run(&mut env::args().skip(1)) {
+        error!(
+            "{}",
+            why.iter_chain()
+                .map(|cause| cause.to_string())
+                .collect::<Vec<String>>()
+                .join(": ")
+        );
+        std::process::exit(1);
     }
 }
 
-fn save_shared_generator_context(
-    shared_generator_context_filepath: &PathBuf,
-    game_iteration: GameIteration,
-    game_score: GameScore,
-    game_board: &GameBoard,
-) {
-    fs::File::create(shared_generator_context_filepath)
-        .expect("Shared generator context file should be writeable.")
-        .write_all(format!("{}\n{}\n{}", game_iteration, game_score, game_board).as_bytes())
-        .expect("Shared generator context file should be writeable.");
-}
-
-fn main() {
-    env_logger::init();
-
-    let mut args = std::env::args().skip(1);
+fn run<I: Iterator<Item = String>>(args: &mut I) -> Result<(), failure::Error> {
     let shared_generator_context_filepath: PathBuf = args.next()
-        .expect(
-            "The first argument to the generator should be a path to the \
-             shared generator context file.",
-        )
+        .ok_or_else(|| {
+            format_err!(
+                "The first argument to the generator should be a path to the shared generator \
+                 context file"
+            )
+        })?
         .into();
     let previous_solution_output_filepath: PathBuf = args.next()
-        .expect(
-            "The second argument to the generator should be a path to the \
-             previous solution output file.",
-        )
+        .ok_or_else(|| {
+            format_err!(
+                "The second argument to the generator should be a path to the previous solution \
+                 output file"
+            )
+        })?
         .into();
-    let moves_per_game = env::var("MOVES_PER_GAME")
-        .ok()
-        .and_then(|str_value| str_value.parse::<GameIteration>().ok())
-        .unwrap_or(25);
+    let secret_seed_filepath: Option<PathBuf> = args.next().map(Into::into);
+    let moves_per_game =
+        parse_env_var::<GameIteration>("MOVES_PER_GAME", "a positive integer")?.unwrap_or(25);
+    if moves_per_game == 0 {
+        bail!("MOVES_PER_GAME is expected to be a positive integer, but 0 is given");
+    }
+    let rules = parse_env_var::<RuleSet>("RULE_SET", "a valid rule set")?.unwrap_or_default();
+    let ball_spawner_description =
+        env::var("BALL_SPAWNER").unwrap_or_else(|_| "uniform".to_string());
+    let spawn_version =
+        parse_env_var::<SpawnVersion>("SPAWN_VERSION", "1 or 2")?.unwrap_or_default();
+    let game_seed =
+        parse_env_var::<u64>("GAME_SEED", "an unsigned 64-bit integer")?.unwrap_or(0);
+    let secret = match secret_seed_filepath {
+        Some(secret_seed_filepath) => {
+            let mut secret = Vec::new();
+            fs::File::open(&secret_seed_filepath)
+                .and_then(|mut file| file.read_to_end(&mut secret))
+                .with_context(|_| {
+                    format!(
+                        "The secret seed file {:?} could not be read",
+                        secret_seed_filepath
+                    )
+                })?;
+            Some(secret)
+        }
+        None => None,
+    };
+    let spawn_seed = secret
+        .as_ref()
+        .map_or(game_seed, |secret| salted_seed(game_seed, secret));
+    let secret_fingerprint_description = secret.as_ref().map_or_else(
+        || "none".to_string(),
+        |secret| format!("{:08x}", secret_fingerprint(secret)),
+    );
+    let preview_mode = parse_env_var::<PreviewMode>("PREVIEW", "off, colors or locations")?
+        .unwrap_or_default();
 
-    let (game_iteration, mut game_score, mut game_board) =
-        load_shared_generator_context(&shared_generator_context_filepath);
-    let mut remaining_balls = if game_iteration % moves_per_game == 0 {
-        5
-    } else {
-        3
+    let _state_lock = GeneratorStateLock::acquire(&shared_generator_context_filepath)?;
+    let mut state = match GeneratorState::load(
+        &shared_generator_context_filepath,
+        rules,
+        game_seed,
+        moves_per_game,
+    )? {
+        Some(state) => state,
+        None => GeneratorState::new(rules, game_seed)?,
     };
+    if state.rules != rules {
+        bail!(
+            "RULE_SET ({}) differs from the rule set the game was started with ({})",
+            rules,
+            state.rules
+        );
+    }
+    if state.game_seed != game_seed {
+        bail!(
+            "GAME_SEED ({}) differs from the seed the game was started with ({})",
+            game_seed,
+            state.game_seed
+        );
+    }
+    if let Some(spawn_rng_version) = state.spawn_rng_version {
+        SpawnRng::check_version(spawn_rng_version)
+            .context("The game was started by a generator with another spawn RNG")?;
+    }
+    state.spawn_rng_version = Some(SpawnRng::VERSION);
+    ensure_unchanged("BALL_SPAWNER", &ball_spawner_description, &state.ball_spawner)?;
+    ensure_unchanged("SPAWN_VERSION", &spawn_version.number(), &state.spawn_version)?;
+    ensure_unchanged("PREVIEW", &preview_mode, &state.preview_mode)?;
+    ensure_unchanged(
+        "The secret seed fingerprint",
+        &secret_fingerprint_description,
+        &state.secret_fingerprint,
+    )?;
+    state.ball_spawner = Some(ball_spawner_description.clone());
+    state.spawn_version = Some(spawn_version.number());
+    state.preview_mode = Some(preview_mode);
+    state.secret_fingerprint = Some(secret_fingerprint_description);
+    let game_iteration = state.game_iteration;
+    let is_new_game = game_iteration % moves_per_game == 0;
+    let start_position_filepath = env::var("START_POSITION").ok();
+    if is_new_game {
+        state.game_index = game_iteration / moves_per_game;
+        state.next_balls = NextBalls::default();
+        let mut game = GameRecord::new(state.game_index);
+        if let Some(ref start_position_filepath) = start_position_filepath {
+            let start_position = fs::read_to_string(start_position_filepath)
+                .map_err(failure::Error::from)
+                .and_then(|start_position_str| {
+                    Ok(GamePosition::parse_with_rules(&start_position_str, rules)?)
+                })
+                .with_context(|_| {
+                    format!(
+                        "START_POSITION file {:?} is not a valid position",
+                        start_position_filepath
+                    )
+                })?;
+            state.game_board = start_position.game_board.clone();
+            state.game_score += start_position.game_score;
+            game.score = start_position.game_score;
+            game.start_position = Some(start_position);
+        } else {
+            state.game_board = GameBoard::with_rules(rules)?;
+        }
+        state.games.push(game);
+    }
+    let spawner = ball_spawner(
+        &ball_spawner_description,
+        rules,
+        spawn_version,
+        spawn_seed,
+        secret.is_some(),
+    )?;
+    let spawner_rng_description = spawner
+        .rng_description()
+        .unwrap_or_else(|| "no RNG".to_string());
+    let mut game_session = GameSession::with_state(
+        state.game_board.clone(),
+        state.game_score,
+        game_iteration,
+        spawner,
+    ).with_preview(preview_mode, state.next_balls.clone())
+        .with_game_over_reason(state.game_over_reason());
 
-    if game_iteration % moves_per_game == 0 {
-        game_board = GameBoard::default();
+    if is_new_game {
+        info!(
+            "New game at iteration {}: BALL_SPAWNER={}, SPAWN_VERSION={}, GAME_SEED={}{}, \
+             spawned with {}",
+            game_iteration,
+            ball_spawner_description,
+            spawn_version,
+            game_seed,
+            if secret.is_some() {
+                " salted with the secret"
+            } else {
+                ""
+            },
+            spawner_rng_description
+        );
+        let turn_outcome = if let Some(start_position_filepath) = start_position_filepath {
+            info!("The game starts from the position {:?}", start_position_filepath);
+            game_session.start_from_position()?
+        } else {
+            game_session.start()?
+        };
+        state.record_turn(
+            game_iteration,
+            None,
+            &turn_outcome.spawned_balls,
+            turn_outcome.score,
+        );
+    } else if let Some(game_over_reason) = game_session.game_over_reason() {
+        info!(
+            "The game is over ({}), so the previous solution output is ignored.",
+            game_over_reason
+        );
     } else {
-        let mut previous_solution_output_is_valid = false;
         let mut previous_solution_output = String::new();
-        if let Err(why) = fs::File::open(&previous_solution_output_filepath)
-            .map(|mut file| file.read_to_string(&mut previous_solution_output))
-        {
-            info!(
-                "Generator could not read the previous solution output file: {:?}",
-                why
-            );
-        } else {
-            match previous_solution_output.parse::<GameBoardMove>() {
-                Ok(player_move) => {
-                    if let Ok(score) = game_board.move_ball(&player_move) {
-                        previous_solution_output_is_valid = true;
-                        if score > GameScore::new(0) {
-                            game_score += score;
-                            remaining_balls = 0;
-                        }
-                    }
-                }
-                Err(why) => {
-                    if game_board.is_full() {
-                        previous_solution_output_is_valid = true;
-                    } else {
-                        info!(
-                            "The previous player move was invalid ({:?}), so we \
-                             fill the board with balls to indicate the game over.",
-                            why
-                        );
-                    }
+        let player_move = fs::File::open(&previous_solution_output_filepath)
+            .and_then(|mut file| file.read_to_string(&mut previous_solution_output))
+            .map_err(|why| format!("the output file could not be read: {}", why))
+            .and_then(|_| {
+                previous_solution_output
+                    .parse::<GameBoardMove>()
+                    .map_err(|why| why.to_string())
+            });
+        // A spawner failure is not the player's fault, so it does not end the game.
+        let turn_outcome = match player_move {
+            Ok(player_move) => match game_session.play(&player_move) {
+                Ok(turn_outcome) => Ok((player_move, turn_outcome)),
+                Err(TurnError::InvalidMove(why)) => Err(why.to_string()),
+                Err(TurnError::Spawn(why)) => return Err(why.into()),
+            },
+            Err(why) => Err(why),
+        };
+        match turn_outcome {
+            Ok((player_move, turn_outcome)) => {
+                state.record_turn(
+                    game_iteration,
+                    Some(player_move),
+                    &turn_outcome.spawned_balls,
+                    turn_outcome.score,
+                );
+                if turn_outcome.move_outcome.is_scored() {
+                    info!(
+                        "The player cleared {:?}",
+                        turn_outcome.move_outcome.cleared_lines
+                    );
                 }
             }
-        }
-        if !previous_solution_output_is_valid {
-            let mut rng = rand::IsaacRng::new_unseeded();
-            while !game_board.is_full() {
-                for location in RandomGameBoardLocationIterator::new(&rng).take(100) {
-                    game_board
-                        .add_ball(&location, random_ball_color(&mut rng))
-                        .ok();
-                }
+            Err(why) => {
+                let game_over_reason = game_session.finish(GameOverReason::InvalidMove);
+                info!(
+                    "The game is over ({}) as the previous player move was invalid: {}",
+                    game_over_reason, why
+                );
             }
-            remaining_balls = 0;
         }
     }
-
-    if remaining_balls > 0 {
-        let mut rng = rand::IsaacRng::from_seed(&[game_iteration; 32]);
-        for location in RandomGameBoardLocationIterator::new(&rng) {
-            if let Ok(score) = game_board.add_ball(&location, random_ball_color(&mut rng)) {
-                game_score += score;
-                remaining_balls -= 1;
-                if remaining_balls == 0 {
-                    break;
-                }
-            } else if game_board.is_full() {
-                break;
-            }
-        }
+    if !game_session.is_game_over() && (game_iteration + 1) % moves_per_game == 0 {
+        let game_over_reason = game_session.finish(GameOverReason::MoveLimit);
+        info!(
+            "The game is over ({}) after {} moves.",
+            game_over_reason, moves_per_game
+        );
     }
 
-    save_shared_generator_context(
-        &shared_generator_context_filepath,
-        game_iteration + 1,
-        game_score,
-        &game_board,
-    );
+    let game_over_reason = game_session.game_over_reason();
+    state.game_iteration = game_iteration + 1;
+    state.game_score = game_session.game_score();
+    state.game_board = game_session.game_board().clone();
+    state.next_balls = game_session.next_balls().clone();
+    if let Some(game) = state.current_game_mut() {
+        game.game_over_reason = game_over_reason;
+    }
+    state.save(&shared_generator_context_filepath)?;
 
-    println!("{}\n{}", game_board, game_score);
+    println!("{}\n{}", game_session.game_board(), game_session.game_score());
+    if let Some(game_over_reason) = game_over_reason {
+        println!("{}{}", GAME_OVER_PREFIX, game_over_reason);
+        return Ok(());
+    }
+    match preview_mode {
+        PreviewMode::Disabled => (),
+        PreviewMode::Colors => println!(
+            "{}",
+            game_session
+                .next_balls()
+                .ball_colors()
+                .map(|ball_color| ball_color.to_char().to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        ),
+        PreviewMode::ColorsAndLocations => println!("{}", game_session.next_balls()),
+    }
+    Ok(())
 }
@@ -0,0 +1,324 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use failure::{self, ResultExt};
+use serde_json;
+
+use lines_game_engine::{BallColor, GameBoard, GameBoardLocation, GameBoardMove, GameIteration,
+                        GameOverReason, GamePosition, GameScore, NextBalls, PreviewMode, RuleSet,
+                        SpawnRng, SpawnedBall, GAME_BOARD_SIZE, GAME_OVER_PREFIX};
+
+#[cfg(test)]
+mod tests;
+
+/// The version of the shared generator context format. Version 1 is the original plain text
+/// format (iteration, score and board lines), which is migrated on load.
+pub const GENERATOR_STATE_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TurnRecord {
+    pub game_iteration: GameIteration,
+    /// The player move that started the turn (there is none on the turn that starts the game).
+    pub player_move: Option<GameBoardMove>,
+    pub spawned_balls: Vec<(GameBoardLocation, BallColor)>,
+    /// The score gained during the turn.
+    pub score: GameScore,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub game_index: u32,
+    /// The position the game has started from, if it was not started with random balls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_position: Option<GamePosition>,
+    pub score: GameScore,
+    pub game_over_reason: Option<GameOverReason>,
+    pub turns: Vec<TurnRecord>,
+}
+
+impl GameRecord {
+    pub fn new(game_index: u32) -> Self {
+        Self {
+            game_index,
+            start_position: None,
+            score: GameScore::new(0),
+            game_over_reason: None,
+            turns: Vec::new(),
+        }
+    }
+}
+
+/// Everything the generator keeps between its runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeneratorState {
+    pub version: u32,
+    /// The public game seed (the secret, if any, is never saved).
+    pub game_seed: u64,
+    pub rules: RuleSet,
+    /// The `SpawnRng` version the game was played with (unknown in the files written before it
+    /// was recorded; the next run records its own).
+    #[serde(default)]
+    pub spawn_rng_version: Option<u32>,
+    /// The `BALL_SPAWNER`, `SPAWN_VERSION` and `PREVIEW` settings the game was played with; they
+    /// are recorded the same way as the RNG version.
+    #[serde(default)]
+    pub ball_spawner: Option<String>,
+    #[serde(default)]
+    pub spawn_version: Option<u32>,
+    #[serde(default)]
+    pub preview_mode: Option<PreviewMode>,
+    /// The fingerprint of the secret the spawn seed was salted with (`none` if it was not), so
+    /// the game is not continued with another secret.
+    #[serde(default)]
+    pub secret_fingerprint: Option<String>,
+    /// The iteration the next generator run is going to make.
+    pub game_iteration: GameIteration,
+    /// The index of the game the last run has played.
+    pub game_index: u32,
+    /// The score of all the games together, as it is shown to the solution.
+    pub game_score: GameScore,
+    pub game_board: GameBoard,
+    pub next_balls: NextBalls,
+    /// The games with their scores and the move/spawn history. The games started before the
+    /// state was migrated from the version 1 format have no records.
+    pub games: Vec<GameRecord>,
+}
+
+impl GeneratorState {
+    pub fn new(rules: RuleSet, game_seed: u64) -> Result<Self, failure::Error> {
+        Ok(Self {
+            version: GENERATOR_STATE_VERSION,
+            game_seed,
+            rules,
+            spawn_rng_version: Some(SpawnRng::VERSION),
+            ball_spawner: None,
+            spawn_version: None,
+            preview_mode: None,
+            secret_fingerprint: None,
+            game_iteration: 0,
+            game_index: 0,
+            game_score: GameScore::new(0),
+            game_board: GameBoard::with_rules(rules)?,
+            next_balls: NextBalls::default(),
+            games: Vec::new(),
+        })
+    }
+
+    /// Loads the state, migrating the files of the older versions; returns `None` if there is
+    /// no state file yet.
+    pub fn load<P: AsRef<Path>>(
+        path: P,
+        rules: RuleSet,
+        game_seed: u64,
+        moves_per_game: GameIteration,
+    ) -> Result<Option<Self>, failure::Error> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let mut state_str = String::new();
+        fs::File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut state_str))
+            .with_context(|_| format!("The generator state file {:?} could not be read", path))?;
+        let state = if state_str.trim_start().starts_with('{') {
+            Self::from_json(&state_str)
+        } else {
+            Self::from_version_1(&state_str, rules, game_seed, moves_per_game)
+        }.with_context(|_| format!("The generator state file {:?} is corrupted", path))?;
+        Ok(Some(state))
+    }
+
+    fn from_json(state_str: &str) -> Result<Self, failure::Error> {
+        let state_value = serde_json::from_str::<serde_json::Value>(state_str)
+            .context("The generator state is not a valid JSON")?;
+        match state_value.get("version").and_then(|version| version.as_u64()) {
+            Some(version) if version == u64::from(GENERATOR_STATE_VERSION) => (),
+            Some(version) => bail!(
+                "The generator state version {} is not supported (the latest is {})",
+                version,
+                GENERATOR_STATE_VERSION
+            ),
+            None => bail!("The generator state has no version"),
+        }
+        let state = serde_json::from_value::<Self>(state_value)
+            .context("The generator state does not match its version format")?;
+        if state.game_board.rules() != &state.rules {
+            bail!("The game board rule set differs from the generator rule set");
+        }
+        if let Some(game) = state.games.last() {
+            if game.game_index != state.game_index {
+                bail!(
+                    "The last game record has index {}, but the current game index is {}",
+                    game.game_index,
+                    state.game_index
+                );
+            }
+        }
+        Ok(state)
+    }
+
+    /// Reads the original text format: the game iteration, the score, the board lines and then
+    /// the optional next balls and `GAME OVER <reason>` lines.
+    fn from_version_1(
+        state_str: &str,
+        rules: RuleSet,
+        game_seed: u64,
+        moves_per_game: GameIteration,
+    ) -> Result<Self, failure::Error> {
+        let mut state_lines = state_str.lines();
+        let game_iteration = state_lines
+            .next()
+            .ok_or_else(|| format_err!("Unexpected EOF on Game Iteration value"))?
+            .parse::<GameIteration>()
+            .context("Game Iteration is expected to be an integer")?;
+        let game_score = state_lines
+            .next()
+            .ok_or_else(|| format_err!("Unexpected EOF on Game Score value"))?
+            .parse::<GameScore>()
+            .context("Game Score is expected to be an integer")?;
+        let game_board = GameBoard::parse_with_rules(
+            &(&mut state_lines)
+                .take(GAME_BOARD_SIZE)
+                .collect::<Vec<&str>>()
+                .join("\n"),
+            rules,
+        ).context("Game Board could not be parsed")?;
+        let mut next_balls = NextBalls::default();
+        let mut game_over_reason = None;
+        for line in state_lines {
+            if let Some(game_over_reason_str) = line.strip_prefix(GAME_OVER_PREFIX) {
+                game_over_reason = Some(game_over_reason_str
+                    .parse::<GameOverReason>()
+                    .context("Game Over reason could not be parsed")?);
+            } else {
+                next_balls = line.parse::<NextBalls>()
+                    .context("Next Balls could not be parsed")?;
+            }
+        }
+        let game_index = game_iteration.saturating_sub(1) / moves_per_game.max(1);
+        Ok(Self {
+            version: GENERATOR_STATE_VERSION,
+            game_seed,
+            rules,
+            spawn_rng_version: None,
+            ball_spawner: None,
+            spawn_version: None,
+            preview_mode: None,
+            secret_fingerprint: None,
+            game_iteration,
+            game_index,
+            game_score,
+            game_board,
+            next_balls,
+            // The score of the current game cannot be told apart from the scores of the
+            // previous ones, so the record starts from zero.
+            games: vec![GameRecord {
+                game_over_reason,
+                ..GameRecord::new(game_index)
+            }],
+        })
+    }
+
+    /// Replaces the state file atomically: the state is written to a temporary file next to it,
+    /// flushed to the disk and renamed over the old one, so a killed run leaves either the old or
+    /// the new state.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), failure::Error> {
+        let path = path.as_ref();
+        let state_str = serde_json::to_string_pretty(self)? + "\n";
+        let temporary_path = sibling_path(path, ".tmp");
+        fs::File::create(&temporary_path)
+            .and_then(|mut file| {
+                file.write_all(state_str.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&temporary_path, path))
+            .with_context(|_| format!("The generator state file {:?} could not be written", path))?;
+        // Make the rename itself durable; not every platform allows to sync a directory.
+        if let Some(directory) = path.parent() {
+            let directory = if directory.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                directory
+            };
+            if let Ok(directory) = fs::File::open(directory) {
+                directory.sync_all().ok();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn current_game(&self) -> Option<&GameRecord> {
+        self.games.last()
+    }
+
+    /// Adds the turn to the current game record and its score.
+    pub fn record_turn(
+        &mut self,
+        game_iteration: GameIteration,
+        player_move: Option<GameBoardMove>,
+        spawned_balls: &[SpawnedBall],
+        score: GameScore,
+    ) {
+        if let Some(game) = self.current_game_mut() {
+            game.score += score;
+            game.turns.push(TurnRecord {
+                game_iteration,
+                player_move,
+                spawned_balls: spawned_balls
+                    .iter()
+                    .map(|spawned_ball| (spawned_ball.location, spawned_ball.ball_color))
+                    .collect(),
+                score,
+            });
+        }
+    }
+
+    pub fn current_game_mut(&mut self) -> Option<&mut GameRecord> {
+        self.games.last_mut()
+    }
+
+    pub fn game_over_reason(&self) -> Option<GameOverReason> {
+        self.current_game().and_then(|game| game.game_over_reason)
+    }
+}
+
+/// An advisory lock of the generator state file, held until it is dropped. The lock is taken on a
+/// separate `<state file>.lock` file, since the state file itself is replaced on every save.
+#[derive(Debug)]
+pub struct GeneratorStateLock {
+    _lock_file: fs::File,
+}
+
+impl GeneratorStateLock {
+    pub fn acquire<P: AsRef<Path>>(path: P) -> Result<Self, failure::Error> {
+        let lock_path = sibling_path(path.as_ref(), ".lock");
+        let lock_file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|_| format!("The lock file {:?} could not be opened", lock_path))?;
+        match lock_file.try_lock() {
+            Ok(()) => Ok(Self {
+                _lock_file: lock_file,
+            }),
+            Err(fs::TryLockError::WouldBlock) => bail!(
+                "The generator state file {:?} is locked by another generator run (the lock file \
+                 is {:?}); the runs sharing a state file must not overlap",
+                path.as_ref(),
+                lock_path
+            ),
+            Err(fs::TryLockError::Error(why)) => Err(why)
+                .with_context(|_| format!("The lock file {:?} could not be locked", lock_path))
+                .map_err(Into::into),
+        }
+    }
+}
+
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut sibling_path = OsString::from(path.as_os_str());
+    sibling_path.push(suffix);
+    sibling_path.into()
+}
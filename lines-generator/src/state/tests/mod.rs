@@ -0,0 +1,218 @@
+use std::fs;
+use std::path::Path;
+
+use failure;
+use serde_json;
+
+use lines_game_engine::{BallColor, GameBoard, GameBoardLocation, GameOverReason, GameScore,
+                        NextBalls, PreviewMode, RuleSet, SpawnRng};
+
+use super::super::testing::TemporaryDirectory;
+use super::{GameRecord, GeneratorState, GeneratorStateLock, GENERATOR_STATE_VERSION};
+
+const EMPTY_BOARD_LINES: &str = "\
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _
+_ _ _ _ _ _ _ _ _";
+
+fn load_str(
+    state_filepath: &Path,
+    state_str: &str,
+) -> Result<Option<GeneratorState>, failure::Error> {
+    fs::write(state_filepath, state_str).unwrap();
+    GeneratorState::load(state_filepath, RuleSet::default(), 7, 25)
+}
+
+fn load_error(state_filepath: &Path, state_str: &str) -> String {
+    load_str(state_filepath, state_str)
+        .unwrap_err()
+        .iter_chain()
+        .map(|cause| cause.to_string())
+        .collect::<Vec<String>>()
+        .join(": ")
+}
+
+#[test]
+fn missing_state_file_is_not_an_error() {
+    let temporary_directory = TemporaryDirectory::new("missing-state");
+    let state_filepath = temporary_directory.join("state.json");
+    assert!(
+        GeneratorState::load(&state_filepath, RuleSet::default(), 7, 25)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn state_survives_save_and_load() {
+    let mut state = GeneratorState::new(RuleSet::default(), 7).unwrap();
+    state.ball_spawner = Some("uniform".to_string());
+    state.spawn_version = Some(2);
+    state.preview_mode = Some(PreviewMode::Colors);
+    state.games.push(GameRecord::new(0));
+    state.record_turn(0, None, &[], GameScore::new(0));
+    state
+        .game_board
+        .add_ball(&GameBoardLocation::from_coords(2, 3).unwrap(), BallColor::Cyan)
+        .unwrap();
+    state.game_iteration = 1;
+    state.next_balls = "1 1 R".parse::<NextBalls>().unwrap();
+
+    let temporary_directory = TemporaryDirectory::new("state-round-trip");
+    let state_filepath = temporary_directory.join("state.json");
+    state.save(&state_filepath).unwrap();
+    let loaded_state = GeneratorState::load(&state_filepath, RuleSet::default(), 7, 25)
+        .unwrap()
+        .unwrap();
+    assert_eq!(loaded_state, state);
+    assert_eq!(loaded_state.version, GENERATOR_STATE_VERSION);
+    assert_eq!(loaded_state.spawn_rng_version, Some(SpawnRng::VERSION));
+}
+
+#[test]
+fn version_1_state_is_migrated() {
+    let temporary_directory = TemporaryDirectory::new("state-v1");
+    let state_filepath = temporary_directory.join("state.txt");
+    let state = load_str(&state_filepath, &format!("27\n35\n{}\n", EMPTY_BOARD_LINES))
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.version, GENERATOR_STATE_VERSION);
+    assert_eq!(state.game_seed, 7);
+    assert_eq!(state.game_iteration, 27);
+    assert_eq!(state.game_score, GameScore::new(35));
+    assert_eq!(state.game_board, GameBoard::default());
+    assert!(state.next_balls.is_empty());
+    assert_eq!(state.game_index, 1);
+    assert_eq!(state.games.len(), 1);
+    assert_eq!(state.games[0].game_index, 1);
+    assert_eq!(state.games[0].game_over_reason, None);
+    assert_eq!(state.spawn_rng_version, None);
+    assert_eq!(state.ball_spawner, None);
+}
+
+#[test]
+fn version_1_state_with_next_balls_and_game_over_is_migrated() {
+    let temporary_directory = TemporaryDirectory::new("state-v1-extra");
+    let state = load_str(
+        &temporary_directory.join("state.txt"),
+        &format!("3\n0\n{}\n1 2 G 3 4 B\nGAME OVER no_space\n", EMPTY_BOARD_LINES),
+    ).unwrap()
+        .unwrap();
+    assert_eq!(state.game_iteration, 3);
+    assert_eq!(
+        state.next_balls,
+        "1 2 G 3 4 B".parse::<NextBalls>().unwrap()
+    );
+    assert_eq!(state.game_over_reason(), Some(GameOverReason::NoSpace));
+}
+
+#[test]
+fn truncated_state_is_reported_as_corrupted() {
+    let state_str = serde_json::to_string(&GeneratorState::new(RuleSet::default(), 7).unwrap())
+        .unwrap();
+    let temporary_directory = TemporaryDirectory::new("truncated-state");
+    let state_filepath = temporary_directory.join("state.json");
+    let error = load_error(&state_filepath, &state_str[..state_str.len() / 2]);
+    assert!(error.contains("is corrupted"), "{}", error);
+    assert!(error.contains("not a valid JSON"), "{}", error);
+
+    let state_filepath = temporary_directory.join("state.txt");
+    let error = load_error(&state_filepath, "3\n0\n_ _ _");
+    assert!(error.contains("is corrupted"), "{}", error);
+    assert!(error.contains("Game Board could not be parsed"), "{}", error);
+
+    let error = load_error(&state_filepath, "3\n");
+    assert!(error.contains("Unexpected EOF on Game Score value"), "{}", error);
+}
+
+#[test]
+fn unknown_state_version_is_refused() {
+    let mut state_value =
+        serde_json::to_value(GeneratorState::new(RuleSet::default(), 7).unwrap()).unwrap();
+    state_value["version"] = serde_json::Value::from(99);
+    let temporary_directory = TemporaryDirectory::new("unknown-state-version");
+    let state_filepath = temporary_directory.join("state.json");
+    let error = load_error(&state_filepath, &state_value.to_string());
+    assert!(
+        error.contains("version 99 is not supported"),
+        "{}",
+        error
+    );
+
+    state_value
+        .as_object_mut()
+        .unwrap()
+        .remove("version");
+    let error = load_error(&state_filepath, &state_value.to_string());
+    assert!(error.contains("has no version"), "{}", error);
+}
+
+#[test]
+fn board_with_other_rules_is_refused() {
+    let mut state = GeneratorState::new(RuleSet::default(), 7).unwrap();
+    state.game_board = GameBoard::with_rules(RuleSet::new(5, 5, 3, 5).unwrap()).unwrap();
+    let temporary_directory = TemporaryDirectory::new("state-with-other-rules");
+    let error = load_error(
+        &temporary_directory.join("state.json"),
+        &serde_json::to_string(&state).unwrap(),
+    );
+    assert!(
+        error.contains("rule set differs from the generator rule set"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn save_leaves_no_temporary_file() {
+    let temporary_directory = TemporaryDirectory::new("state-save");
+    let state_filepath = temporary_directory.join("state.json");
+    let mut state = GeneratorState::new(RuleSet::default(), 7).unwrap();
+    state.save(&state_filepath).unwrap();
+    state.game_iteration = 1;
+    state.save(&state_filepath).unwrap();
+    let filenames = fs::read_dir(temporary_directory.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect::<Vec<_>>();
+    assert_eq!(filenames, vec!["state.json"]);
+}
+
+#[test]
+fn failed_save_keeps_the_previous_state() {
+    let temporary_directory = TemporaryDirectory::new("state-failed-save");
+    let state_filepath = temporary_directory.join("state.json");
+    let mut state = GeneratorState::new(RuleSet::default(), 7).unwrap();
+    state.save(&state_filepath).unwrap();
+    let previous_state_str = fs::read_to_string(&state_filepath).unwrap();
+
+    // The temporary file cannot be created in place of a directory.
+    fs::create_dir(temporary_directory.join("state.json.tmp")).unwrap();
+    state.game_iteration = 1;
+    assert!(state.save(&state_filepath).is_err());
+    assert_eq!(fs::read_to_string(&state_filepath).unwrap(), previous_state_str);
+}
+
+#[test]
+fn state_lock_is_held_until_dropped() {
+    let temporary_directory = TemporaryDirectory::new("state-lock");
+    let state_filepath = temporary_directory.join("state.json");
+    let state_lock = GeneratorStateLock::acquire(&state_filepath).unwrap();
+    let error = GeneratorStateLock::acquire(&state_filepath)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("is locked by another generator run"),
+        "{}",
+        error
+    );
+
+    drop(state_lock);
+    GeneratorStateLock::acquire(&state_filepath).unwrap();
+}
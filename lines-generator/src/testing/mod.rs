@@ -0,0 +1,40 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static TEMPORARY_DIRECTORIES_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory that only one test uses; it is removed with all its files when it is dropped.
+pub struct TemporaryDirectory {
+    path: PathBuf,
+}
+
+impl TemporaryDirectory {
+    pub fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!(
+            "lines-generator-{}-{}-{}",
+            name,
+            process::id(),
+            TEMPORARY_DIRECTORIES_COUNT.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::remove_dir_all(&path).ok();
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join<P: AsRef<Path>>(&self, name: P) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl Drop for TemporaryDirectory {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.path).ok();
+    }
+}
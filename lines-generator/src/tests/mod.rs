@@ -0,0 +1,65 @@
+use std::fs;
+use std::path::Path;
+
+use log;
+
+use lines_game_engine::{GameOverReason, RuleSet};
+
+use super::run;
+use super::state::GeneratorState;
+use super::testing::TemporaryDirectory;
+
+#[test]
+fn invalid_move_ends_the_game_with_logging_disabled() {
+    // No logger is set up in the tests, so the log message arguments are never evaluated.
+    assert!(!log_enabled!(log::Level::Error));
+    let temporary_directory = TemporaryDirectory::new("invalid-move");
+    let state_filepath = temporary_directory.join("state.json");
+    let solution_output_filepath = temporary_directory.join("output.txt");
+    let args = [
+        state_filepath.to_str().unwrap().to_string(),
+        solution_output_filepath.to_str().unwrap().to_string(),
+    ];
+    run(&mut args.iter().cloned()).unwrap();
+    fs::write(&solution_output_filepath, "not a move").unwrap();
+    run(&mut args.iter().cloned()).unwrap();
+
+    let state = GeneratorState::load(&state_filepath, RuleSet::default(), 0, 25)
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.game_iteration, 2);
+    assert_eq!(state.game_over_reason(), Some(GameOverReason::InvalidMove));
+}
+
+#[test]
+fn game_is_not_continued_with_another_secret() {
+    let temporary_directory = TemporaryDirectory::new("secret");
+    let state_filepath = temporary_directory.join("state.json");
+    let solution_output_filepath = temporary_directory.join("output.txt");
+    let secret_filepath = temporary_directory.join("secret");
+    let other_secret_filepath = temporary_directory.join("other-secret");
+    fs::write(&secret_filepath, "jury secret").unwrap();
+    fs::write(&other_secret_filepath, "other jury secret").unwrap();
+    let args = |secret_filepath: Option<&Path>| {
+        let mut args = vec![
+            state_filepath.to_str().unwrap().to_string(),
+            solution_output_filepath.to_str().unwrap().to_string(),
+        ];
+        if let Some(secret_filepath) = secret_filepath {
+            args.push(secret_filepath.to_str().unwrap().to_string());
+        }
+        args
+    };
+    run(&mut args(Some(&secret_filepath)).into_iter()).unwrap();
+
+    for other_args in &[args(None), args(Some(&other_secret_filepath))] {
+        let error = run(&mut other_args.iter().cloned()).unwrap_err().to_string();
+        assert!(
+            error.starts_with("The secret seed fingerprint"),
+            "unexpected error: {}",
+            error
+        );
+    }
+    fs::write(&solution_output_filepath, "not a move").unwrap();
+    run(&mut args(Some(&secret_filepath)).into_iter()).unwrap();
+}
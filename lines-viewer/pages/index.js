@@ -30,7 +30,8 @@ class LinesGameLogViewer extends React.PureComponent {
     return <React.Fragment>
       <div className="lines-score">{currentGame.score}</div>
       <LinesBoard board={currentGame.board} />
-      {this.state.step >= this.props.gameLog.length && <h1>{`The game is over!`}</h1>}
+      {currentGame.gameOverReason && <h1>{`The game is over (${currentGame.gameOverReason})!`}</h1>}
+      {!currentGame.gameOverReason && this.state.step >= this.props.gameLog.length && <h1>{`The game is over!`}</h1>}
     </React.Fragment>
   }
 }
@@ -43,13 +44,24 @@ export default class extends React.Component {
     let gameLog = []
     let gameStartIndex = 0
     while (gameStartIndex < gameLogLines.length) {
-      const gameState = gameLogLines.slice(gameStartIndex, gameStartIndex + 12)
-      gameStartIndex += 12
+      // The optional line after the score either previews the next balls (it has color codes)
+      // or tells that the game is over (`GAME OVER <reason>`).
+      const extraLine = gameLogLines[gameStartIndex + 11] || ''
+      const gameOverMatch = /^GAME OVER (\S+)/.exec(extraLine)
+      const hasPreview = !gameOverMatch && /[A-Z]/.test(extraLine)
+      const moveIndex = gameOverMatch || hasPreview ? 12 : 11
+      const gameState = gameLogLines.slice(gameStartIndex, gameStartIndex + moveIndex + 1)
+      gameStartIndex += moveIndex + 1
       const score = gameState[10]
       const board = gameState.slice(0, 9).map(line => line.split(' '))
+      if (gameOverMatch) {
+        // The solution output is ignored once the game is over.
+        gameLog.push({ board, score, gameOverReason: gameOverMatch[1] })
+        continue
+      }
       gameLog.push({ board, score })
-      if (!gameState[11]) break;
-      const [from_x, from_y, to_x, to_y] = gameState[11].split(' ')
+      if (!gameState[moveIndex]) break;
+      const [from_x, from_y, to_x, to_y] = gameState[moveIndex].split(' ')
       console.log(gameState, from_x, from_y)
       const boardWithMove = board.map(line => [...line])
       boardWithMove[to_y - 1][to_x - 1] = boardWithMove[from_y - 1][from_x - 1]